@@ -0,0 +1,461 @@
+use super::error::DecodeError;
+
+use serde::de::{self, IntoDeserializer, Visitor};
+
+/// Reads values back from their untagged absorbed encoding.
+///
+/// The encoding is not self-describing: values are decoded guided by their type,
+/// exactly mirroring `AbsorbSerializer` (in untagged mode).
+#[derive(Clone)]
+pub(crate) struct AbsorbDeserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> AbsorbDeserializer<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        Self { input }
+    }
+
+    /// Number of bytes not yet consumed
+    pub fn remaining(&self) -> usize {
+        self.input.len()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'de [u8], DecodeError> {
+        if self.input.len() < n {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, tail) = self.input.split_at(n);
+        self.input = tail;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let len = u64::from_le_bytes(self.take_array()?);
+        usize::try_from(len).map_err(|_| DecodeError::Invalid)
+    }
+
+    fn bytes(&mut self) -> Result<&'de [u8], DecodeError> {
+        let len = self.len()?;
+        self.take(len)
+    }
+}
+
+macro_rules! decode_int_impl {
+    ( $name:ident, $visit:ident, $t:tt ) => {
+        fn $name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.$visit($t::from_le_bytes(self.take_array()?))
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut AbsorbDeserializer<'de> {
+    type Error = DecodeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(DecodeError::Unsupported)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.byte()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => Err(DecodeError::Invalid),
+        }
+    }
+
+    decode_int_impl!(deserialize_i8, visit_i8, i8);
+    decode_int_impl!(deserialize_i16, visit_i16, i16);
+    decode_int_impl!(deserialize_i32, visit_i32, i32);
+    decode_int_impl!(deserialize_i64, visit_i64, i64);
+    decode_int_impl!(deserialize_i128, visit_i128, i128);
+
+    decode_int_impl!(deserialize_u8, visit_u8, u8);
+    decode_int_impl!(deserialize_u16, visit_u16, u16);
+    decode_int_impl!(deserialize_u32, visit_u32, u32);
+    decode_int_impl!(deserialize_u64, visit_u64, u64);
+    decode_int_impl!(deserialize_u128, visit_u128, u128);
+
+    decode_int_impl!(deserialize_f32, visit_f32, f32);
+    decode_int_impl!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let v = u32::from_le_bytes(self.take_array()?);
+        visitor.visit_char(char::from_u32(v).ok_or(DecodeError::Invalid)?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let bytes = self.bytes()?;
+        visitor.visit_borrowed_str(core::str::from_utf8(bytes).map_err(|_| DecodeError::Invalid)?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_bytes(self.bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.byte()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => Err(DecodeError::Invalid),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.len()?;
+        visitor.visit_seq(Component { de: self, len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Component { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Component { de: self, len })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.len()?;
+        visitor.visit_map(Component { de: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Component {
+            de: self,
+            len: fields.len(),
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(DecodeError::Unsupported)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(DecodeError::Unsupported)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// elements of a sequence / tuple / struct or entries of a map
+struct Component<'a, 'de> {
+    de: &'a mut AbsorbDeserializer<'de>,
+    len: usize, // remaining elements/entries
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for Component<'a, 'de> {
+    type Error = DecodeError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for Component<'a, 'de> {
+    type Error = DecodeError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut AbsorbDeserializer<'de> {
+    type Error = DecodeError;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let index = u32::from_le_bytes(self.take_array()?);
+        let variant = seed.deserialize(index.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut AbsorbDeserializer<'de> {
+    type Error = DecodeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Component { de: self, len })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Component {
+            de: self,
+            len: fields.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Absorb;
+
+    extern crate std;
+
+    use core::fmt::Debug;
+
+    use serde::{Deserialize, Serialize};
+
+    use std::collections::{BTreeMap, HashMap};
+    use std::string::{String, ToString};
+    use std::vec;
+    use std::vec::Vec;
+
+    fn roundtrip<T: Absorb + for<'de> Deserialize<'de> + PartialEq + Debug>(value: T) {
+        let mut bytes = Vec::new();
+        value.absorb(&mut bytes);
+        let mut de = AbsorbDeserializer::new(&bytes);
+        assert_eq!(T::deserialize(&mut de).unwrap(), value);
+        assert_eq!(de.remaining(), 0);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Message {
+        Unit,
+        Newtype(u32),
+        Tuple(u8, bool),
+        Struct { a: u16, b: Option<i64> },
+    }
+
+    #[test]
+    fn enums() {
+        roundtrip(Message::Unit);
+        roundtrip(Message::Newtype(7));
+        roundtrip(Message::Tuple(1, true));
+        roundtrip(Message::Struct { a: 2, b: Some(-3) });
+        roundtrip(vec![Message::Unit, Message::Struct { a: 0, b: None }]);
+
+        // unknown variant
+        let bytes = 4u32.to_le_bytes();
+        let mut de = AbsorbDeserializer::new(&bytes);
+        assert!(Message::deserialize(&mut de).is_err());
+    }
+
+    #[test]
+    fn options() {
+        roundtrip(None::<u8>);
+        roundtrip(Some(5u8));
+        roundtrip(Some(Some(String::from("abc"))));
+        roundtrip((Some(1u16), None::<u16>));
+
+        // the tag must be 0 or 1
+        let mut de = AbsorbDeserializer::new(&[2, 0]);
+        assert_eq!(
+            Option::<u8>::deserialize(&mut de),
+            Err(DecodeError::Invalid)
+        );
+    }
+
+    #[test]
+    fn maps() {
+        roundtrip(BTreeMap::from([(1u8, 2u64), (0u8, 3u64)]));
+        roundtrip(BTreeMap::<u8, u8>::new());
+
+        // hash maps are encoded sorted, decoding restores the entries
+        let map: HashMap<u32, String> = (0u32..16).map(|i| (i, i.to_string())).collect();
+        roundtrip(map);
+    }
+
+    // sequence serialized without a known length
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(transparent)]
+    struct Evens(Vec<u64>);
+
+    impl Serialize for Evens {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            // a filter has no exact size hint
+            serializer.collect_seq(self.0.iter().filter(|x| *x % 2 == 0))
+        }
+    }
+
+    #[test]
+    fn unknown_length_sequences() {
+        roundtrip(Evens(vec![0, 2, 4]));
+        roundtrip(Evens(vec![]));
+
+        // encoded like a sequence of known length
+        let mut unknown = Vec::new();
+        Evens(vec![0, 1, 2, 3, 4]).absorb(&mut unknown);
+        let mut known = Vec::new();
+        vec![0u64, 2, 4].absorb(&mut known);
+        assert_eq!(unknown, known);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bytes = Vec::new();
+        (1u32, String::from("abc")).absorb(&mut bytes);
+        for n in 0..bytes.len() {
+            let mut de = AbsorbDeserializer::new(&bytes[..n]);
+            assert_eq!(
+                <(u32, String)>::deserialize(&mut de),
+                Err(DecodeError::UnexpectedEnd)
+            );
+        }
+    }
+
+    #[cfg(feature = "ff")]
+    #[test]
+    fn ff() {
+        use crate::Ff;
+        use ff::Field;
+        use pasta_curves::Fp;
+
+        roundtrip(Ff(Fp::from(42)));
+        roundtrip(vec![Ff(-Fp::ONE), Ff(Fp::ZERO)]);
+
+        // non-canonical encoding of the modulus
+        let mut bytes = Vec::new();
+        Ff(-Fp::ONE).absorb(&mut bytes);
+        let last = bytes.len() - 32;
+        bytes[last] += 1;
+        let mut de = AbsorbDeserializer::new(&bytes);
+        assert!(Ff::<Fp>::deserialize(&mut de).is_err());
+    }
+
+    #[cfg(feature = "ark")]
+    #[test]
+    fn ark() {
+        use crate::Ark;
+        use ark_ff::{Fp64, MontBackend, MontConfig};
+
+        #[derive(MontConfig)]
+        #[modulus = "18446744069414584321"]
+        #[generator = "7"]
+        struct GoldilocksConfig;
+
+        type F = Fp64<MontBackend<GoldilocksConfig, 1>>;
+
+        roundtrip(Ark(F::from(42u64)));
+        roundtrip((Ark(-F::from(1u64)), Ark(vec![1u32, 2u32])));
+
+        // p - 1 is the largest valid encoding
+        let p = u64::MAX - u32::MAX as u64 + 1;
+        let mut bytes = Vec::new();
+        Ark(p - 1).absorb(&mut bytes);
+        let mut de = AbsorbDeserializer::new(&bytes);
+        assert_eq!(Ark::<F>::deserialize(&mut de), Ok(Ark(-F::from(1u64))));
+
+        let mut bytes = Vec::new();
+        Ark(p).absorb(&mut bytes);
+        let mut de = AbsorbDeserializer::new(&bytes);
+        assert!(Ark::<F>::deserialize(&mut de).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use core::fmt;
+
+use serde::ser;
+
+// maximum number of path segments recorded in an error
+// (kept small, since the error is returned through every level of serialization)
+const MAX_DEPTH: usize = 4;
+
+/// Segment of the path to the value which failed to absorb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    /// Named field of a struct (or struct variant)
+    Field(&'static str),
+    /// Element of a sequence / tuple or entry of a map
+    Index(usize),
+    /// Enum variant
+    Variant(&'static str),
+}
+
+/// Reason absorption failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// A `Serialize` implementation returned a custom error
+    Custom,
+    /// A sequence or map did not report its length
+    UnknownLength,
+    /// The value uses a serde data type which is not supported
+    /// (maps without the `alloc` feature)
+    Unsupported,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Custom => write!(f, "custom serialization error"),
+            Reason::UnknownLength => write!(f, "length of sequence/map is unknown"),
+            Reason::Unsupported => write!(f, "serde data type is not supported"),
+        }
+    }
+}
+
+/// Error returned when a value cannot be absorbed.
+///
+/// Records the reason and (up to 4 segments of) the path to the failing value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbsorbError {
+    reason: Reason,
+    path: [Option<Segment>; MAX_DEPTH], // innermost segment first
+    depth: usize,
+    truncated: bool,
+}
+
+impl AbsorbError {
+    pub(crate) fn new(reason: Reason) -> Self {
+        Self {
+            reason,
+            path: [None; MAX_DEPTH],
+            depth: 0,
+            truncated: false,
+        }
+    }
+
+    /// Add a segment to the path (as the error propagates outwards)
+    pub(crate) fn at(mut self, seg: Segment) -> Self {
+        if self.depth < MAX_DEPTH {
+            self.path[self.depth] = Some(seg);
+            self.depth += 1;
+        } else {
+            self.truncated = true;
+        }
+        self
+    }
+
+    pub fn reason(&self) -> Reason {
+        self.reason
+    }
+
+    /// Path to the failing value, outermost segment first.
+    ///
+    /// If the value is nested more than 4 levels deep
+    /// the outermost segments are omitted.
+    pub fn path(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.path[..self.depth].iter().rev().flatten().copied()
+    }
+}
+
+impl fmt::Display for AbsorbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to absorb ")?;
+        if self.truncated {
+            write!(f, "..")?;
+        }
+        write!(f, "$")?;
+        for seg in self.path() {
+            match seg {
+                Segment::Field(name) => write!(f, ".{}", name)?,
+                Segment::Index(i) => write!(f, "[{}]", i)?,
+                Segment::Variant(name) => write!(f, "::{}", name)?,
+            }
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+impl ser::StdError for AbsorbError {}
+
+impl ser::Error for AbsorbError {
+    fn custom<T>(_msg: T) -> Self {
+        Self::new(Reason::Custom)
+    }
+}
+
+/// Error returned when a value cannot be read back from its (untagged) absorbed encoding.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended before the value was complete
+    UnexpectedEnd,
+    /// Bytes remain after the last value
+    TrailingBytes(usize),
+    /// The input is not a valid encoding (e.g. a bool which is not 0/1)
+    Invalid,
+    /// The type uses a serde data type which cannot be decoded (e.g. `deserialize_any`)
+    Unsupported,
+    /// A `Deserialize` implementation returned a custom error
+    Custom(alloc::string::String),
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::TrailingBytes(n) => write!(f, "{} trailing bytes", n),
+            DecodeError::Invalid => write!(f, "invalid encoding"),
+            DecodeError::Unsupported => write!(f, "serde data type is not supported"),
+            DecodeError::Custom(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ser::StdError for DecodeError {}
+
+#[cfg(feature = "alloc")]
+impl serde::de::Error for DecodeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DecodeError::Custom(alloc::string::ToString::to_string(&msg))
+    }
+}
@@ -0,0 +1,71 @@
+/// A hasher which absorbs field elements natively,
+/// e.g. an algebraic sponge.
+pub trait FieldHasher<F> {
+    fn write_field(&mut self, elems: &[F]);
+}
+
+/// A sampler which squeezes field elements natively.
+pub trait FieldSampler<F> {
+    fn squeeze_field(&mut self) -> F;
+}
+
+/// Types which are absorbed as a sequence of field elements.
+///
+/// Unlike `Absorb` no lengths are absorbed:
+/// the number of elements must be fixed by the type.
+pub trait AbsorbField<F> {
+    fn absorb_field<H: FieldHasher<F>>(&self, h: &mut H);
+}
+
+impl<F, const N: usize> AbsorbField<F> for [F; N] {
+    fn absorb_field<H: FieldHasher<F>>(&self, h: &mut H) {
+        h.write_field(self)
+    }
+}
+
+impl<F, T: AbsorbField<F> + ?Sized> AbsorbField<F> for &T {
+    fn absorb_field<H: FieldHasher<F>>(&self, h: &mut H) {
+        (*self).absorb_field(h)
+    }
+}
+
+macro_rules! absorb_field_tuple_impl {
+    ( $( $t:ident : $i:tt ),* ) => {
+        impl<F, $( $t: AbsorbField<F> ),*> AbsorbField<F> for ( $( $t, )* ) {
+            fn absorb_field<H: FieldHasher<F>>(&self, h: &mut H) {
+                $( self.$i.absorb_field(h); )*
+            }
+        }
+    };
+}
+
+absorb_field_tuple_impl!(A: 0);
+absorb_field_tuple_impl!(A: 0, B: 1);
+absorb_field_tuple_impl!(A: 0, B: 1, C: 2);
+absorb_field_tuple_impl!(A: 0, B: 1, C: 2, D: 3);
+
+/// Canonical packing adapter:
+/// absorbs field elements into a byte-oriented `Hasher`
+/// as their canonical representation in little-endian order.
+#[cfg(feature = "ff")]
+pub struct Packed<'a, H: super::Hasher>(pub &'a mut H);
+
+#[cfg(feature = "ff")]
+impl<'a, F: ff::PrimeField, H: super::Hasher> FieldHasher<F> for Packed<'a, H> {
+    fn write_field(&mut self, elems: &[F]) {
+        let le = repr_le::<F>();
+        for elem in elems {
+            let mut repr = elem.to_repr();
+            if !le {
+                repr.as_mut().reverse();
+            }
+            self.0.write(repr.as_ref());
+        }
+    }
+}
+
+/// Whether the canonical representation is little-endian.
+#[cfg(feature = "ff")]
+pub(crate) fn repr_le<F: ff::PrimeField>() -> bool {
+    F::ONE.to_repr().as_ref()[0] == 1
+}
@@ -1,22 +1,180 @@
+mod error;
 mod serialize;
 
+// reading values back from the absorbed encoding
+#[cfg(feature = "alloc")]
+mod deserialize;
+
 // more absorb impl. (beyond serde serializable types)
 mod more;
 
+// field-element-native absorption
+mod field;
+
+pub use error::{AbsorbError, Reason, Segment};
+
+#[cfg(feature = "alloc")]
+pub use error::DecodeError;
+
+#[cfg(feature = "alloc")]
+pub(crate) use deserialize::AbsorbDeserializer;
+pub use field::{AbsorbField, FieldHasher, FieldSampler};
+
+#[cfg(feature = "ff")]
+pub use field::Packed;
+
+#[cfg(feature = "poseidon")]
+pub(crate) use field::repr_le;
+
 use serde::Serialize;
 
 use serialize::AbsorbSerializer;
 
 pub trait Hasher {
+    /// Absorb identifiers of types, fields and variants
+    /// in addition to the (compact) default encoding.
+    const TAGGED: bool = false;
+
     fn write(&mut self, buf: &[u8]);
 }
 
+#[cfg(feature = "alloc")]
+impl Hasher for alloc::vec::Vec<u8> {
+    fn write(&mut self, buf: &[u8]) {
+        self.extend(buf)
+    }
+}
+
+/// Lengths are always absorbed as a u64 in little-endian,
+/// such that the encoding is independent of the width of usize on the target.
+pub(crate) fn absorb_len<H: Hasher>(h: &mut H, len: usize) {
+    h.write(&(len as u64).to_le_bytes())
+}
+
+/// Types which can be absorbed into a `Hasher`.
+///
+/// The encoding of serde types is platform-independent:
+/// integers are absorbed in little-endian and every length
+/// (of strings, bytes, sequences and maps) as a u64,
+/// e.g. `"ab"` absorbs as `02 00 00 00 00 00 00 00 61 62` on every target.
+///
+/// Map entries are absorbed sorted by the encoding of their keys,
+/// hence maps with equal contents absorb identically regardless of iteration order.
+/// Sorting buffers the entries, hence maps cannot be absorbed without the `alloc` feature
+/// (`Reason::Unsupported`), rather than absorbing differently depending on the features.
+///
+/// When `Hasher::TAGGED` is set, the identifiers of structs, fields, enums and variants
+/// are also absorbed (as length-prefixed strings) preceding the corresponding value.
 pub trait Absorb {
+    /// Absorb the value, panics if the value cannot be absorbed.
     fn absorb<H: Hasher>(&self, h: &mut H);
+
+    /// Absorb the value, returns an error if the value cannot be absorbed.
+    ///
+    /// On error a prefix of the encoding may already have been written to the hasher.
+    fn try_absorb<H: Hasher>(&self, h: &mut H) -> Result<(), AbsorbError> {
+        self.absorb(h);
+        Ok(())
+    }
 }
 
 impl<T: Serialize> Absorb for T {
     fn absorb<H: Hasher>(&self, h: &mut H) {
-        self.serialize(&mut AbsorbSerializer { h }).unwrap();
+        if let Err(err) = self.try_absorb(h) {
+            panic!("{}", err)
+        }
+    }
+
+    fn try_absorb<H: Hasher>(&self, h: &mut H) -> Result<(), AbsorbError> {
+        self.serialize(&mut AbsorbSerializer {
+            h,
+            tagged: H::TAGGED,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+
+    use std::collections::BTreeMap;
+    use std::vec;
+    use std::vec::Vec;
+
+    // hasher collecting the encoding (also without alloc)
+    struct Buf(Vec<u8>);
+
+    impl Hasher for Buf {
+        fn write(&mut self, buf: &[u8]) {
+            self.0.extend_from_slice(buf)
+        }
+    }
+
+    fn encode<A: Absorb>(value: &A) -> Vec<u8> {
+        let mut buf = Buf(Vec::new());
+        value.absorb(&mut buf);
+        buf.0
+    }
+
+    #[test]
+    fn encode_usize() {
+        assert_eq!(encode(&5usize), [5, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn encode_vec() {
+        assert_eq!(
+            encode(&vec![1u16, 2u16]),
+            [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0]
+        );
+        assert_eq!(encode(&Vec::<u8>::new()), [0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn encode_str() {
+        assert_eq!(encode(&"ab"), [2, 0, 0, 0, 0, 0, 0, 0, 0x61, 0x62]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn encode_map() {
+        // sorted by the encoding of the keys
+        let map = BTreeMap::from([(1u8, 2u8), (0u8, 3u8)]);
+        assert_eq!(encode(&map), [2, 0, 0, 0, 0, 0, 0, 0, 0, 3, 1, 2]);
+
+        // which differs from the order of the map for strings and signed integers
+        let map = BTreeMap::from([("aa", 1u8), ("b", 2u8)]);
+        let mut expected = vec![2, 0, 0, 0, 0, 0, 0, 0];
+        expected.extend([1, 0, 0, 0, 0, 0, 0, 0, b'b', 2]);
+        expected.extend([2, 0, 0, 0, 0, 0, 0, 0, b'a', b'a', 1]);
+        assert_eq!(encode(&map), expected);
+
+        let map = BTreeMap::from([(-1i8, 1u8), (1i8, 2u8)]);
+        assert_eq!(encode(&map), [2, 0, 0, 0, 0, 0, 0, 0, 1, 2, 0xff, 1]);
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn encode_map() {
+        // maps are not supported, rather than absorbed in a different order
+        let map = BTreeMap::from([(1u8, 2u8), (0u8, 3u8)]);
+        let err = map.try_absorb(&mut Buf(Vec::new())).unwrap_err();
+        assert_eq!(err.reason(), Reason::Unsupported);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn encode_hash_map() {
+        use std::collections::HashMap;
+
+        // same contents, inserted in different orders (and with different hash seeds)
+        let a: HashMap<u32, u32> = (0..64).map(|i| (i, i * i)).collect();
+        let b: HashMap<u32, u32> = (0..64).rev().map(|i| (i, i * i)).collect();
+        assert_eq!(encode(&a), encode(&b));
+
+        let sorted: BTreeMap<u32, u32> = (0..64).map(|i| (i, i * i)).collect();
+        assert_eq!(encode(&a), encode(&sorted));
     }
 }
@@ -1,10 +1,18 @@
-use super::{Absorb, Hasher};
+use super::{absorb_len, Absorb, AbsorbError, Hasher, Segment};
 
 impl<T: Absorb> Absorb for [T] {
     fn absorb<H: Hasher>(&self, h: &mut H) {
-        self.len().absorb(h);
+        absorb_len(h, self.len());
         for elem in self.iter() {
             elem.absorb(h)
         }
     }
+
+    fn try_absorb<H: Hasher>(&self, h: &mut H) -> Result<(), AbsorbError> {
+        absorb_len(h, self.len());
+        for (i, elem) in self.iter().enumerate() {
+            elem.try_absorb(h).map_err(|e| e.at(Segment::Index(i)))?;
+        }
+        Ok(())
+    }
 }
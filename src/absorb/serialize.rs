@@ -1,9 +1,14 @@
-use crate::{Absorb, Hasher};
+use super::absorb_len;
+use super::error::{AbsorbError, Reason, Segment};
+use crate::Hasher;
 
 use serde::Serialize;
 
 use core::fmt;
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use serde::ser;
 
 const OPTION_NONE: u8 = 0;
@@ -11,26 +16,45 @@ const OPTION_SOME: u8 = 1;
 
 pub(super) struct AbsorbSerializer<'a, H: Hasher> {
     pub h: &'a mut H,
+    pub tagged: bool, // absorb type, field and variant identifiers
 }
 
 pub(super) struct AbsorbComponent<'a, 'b, H: Hasher> {
     ser: &'b mut AbsorbSerializer<'a, H>,
+    idx: usize, // index of the next element/entry
+
+    // elements of a sequence/map of unknown length:
+    // absorbed (after the length) once the sequence/map ends.
+    #[cfg(feature = "alloc")]
+    buf: Option<Vec<u8>>,
+
+    // entries (encoded key, encoded value) of a map:
+    // absorbed in canonical order (sorted by encoded key) once the map ends.
+    #[cfg(feature = "alloc")]
+    entries: Option<Vec<(Vec<u8>, Vec<u8>)>>,
 }
 
-#[derive(Debug)]
-pub(super) struct AbsorbError {}
+// counts the length of a formatted value
+struct FmtCounter(usize);
 
-impl fmt::Display for AbsorbError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "AbsorbError")
+impl fmt::Write for FmtCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
     }
 }
 
-impl ser::StdError for AbsorbError {}
+// writes a formatted value to the hasher
+struct FmtWriter<'a, H: Hasher> {
+    h: &'a mut H,
+    len: usize,
+}
 
-impl ser::Error for AbsorbError {
-    fn custom<T>(_msg: T) -> Self {
-        Self {}
+impl<'a, H: Hasher> fmt::Write for FmtWriter<'a, H> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.h.write(s.as_bytes());
+        self.len += s.len();
+        Ok(())
     }
 }
 
@@ -38,6 +62,51 @@ impl<'a, H: Hasher> AbsorbSerializer<'a, H> {
     fn serialize_byte(&mut self, v: u8) {
         self.h.write(&[v]);
     }
+
+    /// Absorb an identifier (in tagged mode)
+    fn tag(&mut self, ident: &'static str) {
+        if self.tagged {
+            absorb_len(self.h, ident.len());
+            self.h.write(ident.as_bytes());
+        }
+    }
+
+    /// Absorb the identifiers of an enum variant (in tagged mode) followed by its index
+    fn variant(&mut self, name: &'static str, index: u32, variant: &'static str) {
+        self.tag(name);
+        self.tag(variant);
+        self.h.write(&index.to_le_bytes());
+    }
+
+    /// Serializer for nested buffers, in the same mode
+    #[cfg(feature = "alloc")]
+    fn nested<'c>(&self, buf: &'c mut Vec<u8>) -> AbsorbSerializer<'c, Vec<u8>> {
+        AbsorbSerializer {
+            h: buf,
+            tagged: self.tagged,
+        }
+    }
+
+    /// Sequences/maps of unknown length are buffered
+    /// such that the encoding matches that of known length.
+    fn serialize_sequence<'b>(
+        &'b mut self,
+        len: Option<usize>,
+    ) -> Result<AbsorbComponent<'a, 'b, H>, AbsorbError> {
+        match len {
+            Some(len) => {
+                absorb_len(self.h, len);
+                Ok(AbsorbComponent::new(self))
+            }
+            #[cfg(feature = "alloc")]
+            None => Ok(AbsorbComponent {
+                buf: Some(Vec::new()),
+                ..AbsorbComponent::new(self)
+            }),
+            #[cfg(not(feature = "alloc"))]
+            None => Err(AbsorbError::new(Reason::UnknownLength)),
+        }
+    }
 }
 
 #[macro_export]
@@ -66,7 +135,8 @@ impl<'a, 'b, H: Hasher> serde::Serializer for &'b mut AbsorbSerializer<'a, H> {
         Ok(())
     }
 
-    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Self::Error> {
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), Self::Error> {
+        self.tag(name);
         Ok(())
     }
 
@@ -87,11 +157,25 @@ impl<'a, 'b, H: Hasher> serde::Serializer for &'b mut AbsorbSerializer<'a, H> {
     absorb_int_impl!(serialize_u64, u64);
     absorb_int_impl!(serialize_u128, u128);
 
-    fn collect_str<T: ?Sized>(self, _value: &T) -> Result<Self::Ok, Self::Error>
+    fn collect_str<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: fmt::Display,
+        T: ?Sized + fmt::Display,
     {
-        unimplemented!("not supported")
+        // same encoding as serialize_str, without buffering:
+        // format once to obtain the length and again to absorb the bytes.
+        let mut counter = FmtCounter(0);
+        fmt::write(&mut counter, format_args!("{}", value))
+            .map_err(|_| AbsorbError::new(Reason::Custom))?;
+        absorb_len(self.h, counter.0);
+
+        let mut writer = FmtWriter { h: self.h, len: 0 };
+        fmt::write(&mut writer, format_args!("{}", value))
+            .map_err(|_| AbsorbError::new(Reason::Custom))?;
+        if writer.len != counter.0 {
+            // non-deterministic Display implementation
+            return Err(AbsorbError::new(Reason::Custom));
+        }
+        Ok(())
     }
 
     fn serialize_f32(self, value: f32) -> Result<(), Self::Error> {
@@ -114,7 +198,7 @@ impl<'a, 'b, H: Hasher> serde::Serializer for &'b mut AbsorbSerializer<'a, H> {
 
     fn serialize_bytes(self, v: &[u8]) -> Result<(), Self::Error> {
         // (length || bytes)
-        v.len().absorb(self.h);
+        absorb_len(self.h, v.len());
         self.h.write(v);
         Ok(())
     }
@@ -124,100 +208,109 @@ impl<'a, 'b, H: Hasher> serde::Serializer for &'b mut AbsorbSerializer<'a, H> {
         Ok(())
     }
 
-    fn serialize_some<T: ?Sized>(self, v: &T) -> Result<(), Self::Error>
+    fn serialize_some<T>(self, v: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         self.serialize_byte(OPTION_SOME);
         v.serialize(self)
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        let len = len.expect("sequence must have length");
-        len.absorb(self.h);
-        Ok(AbsorbComponent { ser: self })
+        self.serialize_sequence(len)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        Ok(AbsorbComponent { ser: self })
+        Ok(AbsorbComponent::new(self))
     }
 
     fn serialize_tuple_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Ok(AbsorbComponent { ser: self })
+        self.tag(name);
+        Ok(AbsorbComponent::new(self))
     }
 
     fn serialize_tuple_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        self.serialize_u32(variant_index)?;
-        Ok(AbsorbComponent { ser: self })
+        self.variant(name, variant_index, variant);
+        Ok(AbsorbComponent::new(self))
     }
 
-    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        let len = len.expect("sequence must have length");
-        len.absorb(self.h);
-        Ok(AbsorbComponent { ser: self })
+    /// Maps are absorbed in canonical order (sorted by the encoding of the keys),
+    /// which requires buffering every entry: without alloc maps are not supported.
+    #[cfg(feature = "alloc")]
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(AbsorbComponent {
+            entries: Some(Vec::new()),
+            ..AbsorbComponent::new(self)
+        })
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(AbsorbError::new(Reason::Unsupported))
     }
 
     fn serialize_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(AbsorbComponent { ser: self })
+        self.tag(name);
+        Ok(AbsorbComponent::new(self))
     }
 
     fn serialize_struct_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        self.serialize_u32(variant_index)?;
-        Ok(AbsorbComponent { ser: self })
+        self.variant(name, variant_index, variant);
+        Ok(AbsorbComponent::new(self))
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
-        self,
-        _name: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::ser::Serialize,
+        T: ?Sized + serde::ser::Serialize,
     {
+        self.tag(name);
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<(), Self::Error>
     where
-        T: serde::ser::Serialize,
+        T: ?Sized + serde::ser::Serialize,
     {
-        self.serialize_u32(variant_index)?;
-        value.serialize(self)
+        self.variant(name, variant_index, variant);
+        value
+            .serialize(self)
+            .map_err(|e| e.at(Segment::Variant(variant)))
     }
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<(), Self::Error> {
-        self.serialize_u32(variant_index)
+        self.variant(name, variant_index, variant);
+        Ok(())
     }
 
     fn is_human_readable(&self) -> bool {
@@ -225,6 +318,57 @@ impl<'a, 'b, H: Hasher> serde::Serializer for &'b mut AbsorbSerializer<'a, H> {
     }
 }
 
+impl<'a, 'b, H: Hasher> AbsorbComponent<'a, 'b, H> {
+    fn new(ser: &'b mut AbsorbSerializer<'a, H>) -> Self {
+        AbsorbComponent {
+            ser,
+            idx: 0,
+            #[cfg(feature = "alloc")]
+            buf: None,
+            #[cfg(feature = "alloc")]
+            entries: None,
+        }
+    }
+
+    fn write<T>(&mut self, value: &T) -> Result<(), AbsorbError>
+    where
+        T: ?Sized + Serialize,
+    {
+        #[cfg(feature = "alloc")]
+        if let Some(buf) = self.buf.as_mut() {
+            return value.serialize(&mut self.ser.nested(buf));
+        }
+        value.serialize(&mut *self.ser)
+    }
+
+    fn element<T>(&mut self, value: &T) -> Result<(), AbsorbError>
+    where
+        T: ?Sized + Serialize,
+    {
+        let idx = self.idx;
+        self.idx += 1;
+        self.write(value).map_err(|e| e.at(Segment::Index(idx)))
+    }
+
+    fn finish(self) -> Result<(), AbsorbError> {
+        #[cfg(feature = "alloc")]
+        if let Some(buf) = self.buf {
+            absorb_len(self.ser.h, self.idx);
+            self.ser.h.write(&buf);
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(mut entries) = self.entries {
+            entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+            absorb_len(self.ser.h, entries.len());
+            for (key, value) in entries {
+                self.ser.h.write(&key);
+                self.ser.h.write(&value);
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<'a, 'b, H: Hasher> ser::SerializeSeq for AbsorbComponent<'a, 'b, H> {
     type Ok = ();
     type Error = AbsorbError;
@@ -233,11 +377,11 @@ impl<'a, 'b, H: Hasher> ser::SerializeSeq for AbsorbComponent<'a, 'b, H> {
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        self.element(value)
     }
 
     fn end(self) -> Result<(), Self::Error> {
-        Ok(())
+        self.finish()
     }
 }
 
@@ -249,7 +393,7 @@ impl<'a, 'b, H: Hasher> ser::SerializeTuple for AbsorbComponent<'a, 'b, H> {
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        self.element(value)
     }
 
     fn end(self) -> Result<(), Self::Error> {
@@ -265,7 +409,7 @@ impl<'a, 'b, H: Hasher> ser::SerializeTupleStruct for AbsorbComponent<'a, 'b, H>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        self.element(value)
     }
 
     fn end(self) -> Result<(), Self::Error> {
@@ -281,7 +425,7 @@ impl<'a, 'b, H: Hasher> ser::SerializeTupleVariant for AbsorbComponent<'a, 'b, H
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        self.element(value)
     }
 
     fn end(self) -> Result<(), Self::Error> {
@@ -297,18 +441,35 @@ impl<'a, 'b, H: Hasher> ser::SerializeMap for AbsorbComponent<'a, 'b, H> {
     where
         T: ?Sized + Serialize,
     {
-        key.serialize(&mut *self.ser)
+        let idx = self.idx;
+        #[cfg(feature = "alloc")]
+        if let Some(entries) = self.entries.as_mut() {
+            let mut buf = Vec::new();
+            key.serialize(&mut self.ser.nested(&mut buf))
+                .map_err(|e| e.at(Segment::Index(idx)))?;
+            entries.push((buf, Vec::new()));
+            return Ok(());
+        }
+        self.write(key).map_err(|e| e.at(Segment::Index(idx)))
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        #[cfg(feature = "alloc")]
+        if let Some((_, buf)) = self.entries.as_mut().and_then(|e| e.last_mut()) {
+            let idx = self.idx;
+            self.idx += 1;
+            return value
+                .serialize(&mut self.ser.nested(buf))
+                .map_err(|e| e.at(Segment::Index(idx)));
+        }
+        self.element(value)
     }
 
     fn end(self) -> Result<(), Self::Error> {
-        Ok(())
+        self.finish()
     }
 }
 
@@ -316,11 +477,14 @@ impl<'a, 'b, H: Hasher> ser::SerializeStruct for AbsorbComponent<'a, 'b, H> {
     type Ok = ();
     type Error = AbsorbError;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        self.ser.tag(key);
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|e| e.at(Segment::Field(key)))
     }
 
     fn end(self) -> Result<(), Self::Error> {
@@ -332,11 +496,14 @@ impl<'a, 'b, H: Hasher> ser::SerializeStructVariant for AbsorbComponent<'a, 'b,
     type Ok = ();
     type Error = AbsorbError;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut *self.ser)
+        self.ser.tag(key);
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|e| e.at(Segment::Field(key)))
     }
 
     fn end(self) -> Result<(), Self::Error> {
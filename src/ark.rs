@@ -0,0 +1,115 @@
+use crate::Challenge;
+
+use alloc::vec::Vec;
+
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use rand_core::{CryptoRng, RngCore};
+
+use serde::{de, ser, Deserialize, Serialize};
+
+// additional bytes sampled for challenges, such that the bias after reduction is 2^-128
+const CHALLENGE_SLACK: usize = 16;
+
+/// Bridge for arkworks types.
+///
+/// Serializes (and hence absorbs) the wrapped value as the bytes of its
+/// compressed canonical serialization, deserialization validates the value.
+/// This allows e.g. `Msg<Ark<G1Affine>>` for any `CanonicalSerialize` type,
+/// without a newtype per type.
+///
+/// For prime fields `ts.challenge::<Ark<Fr>>()` samples a field element
+/// by reducing `ceil(MODULUS_BIT_SIZE / 8) + 16` uniform bytes (bias 2^-128).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct Ark<T>(pub T);
+
+impl<T> Ark<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Ark<T> {
+    fn from(value: T) -> Self {
+        Ark(value)
+    }
+}
+
+impl<T> Deref for Ark<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Ark<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: CanonicalSerialize> Serialize for Ark<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut bytes = Vec::with_capacity(self.0.compressed_size());
+        self.0
+            .serialize_compressed(&mut bytes)
+            .map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "canonical compressed bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(v) = seq.next_element()? {
+            bytes.push(v);
+        }
+        Ok(bytes)
+    }
+}
+
+impl<'de, T: CanonicalDeserialize> Deserialize<'de> for Ark<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let bytes = deserializer.deserialize_bytes(BytesVisitor)?;
+        T::deserialize_compressed(&bytes[..])
+            .map(Ark)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<F: PrimeField> Challenge for Ark<F> {
+    fn sample<S: CryptoRng + RngCore>(ts: &mut S) -> Self {
+        let mut bytes =
+            alloc::vec![0u8; (F::MODULUS_BIT_SIZE as usize).div_ceil(8) + CHALLENGE_SLACK];
+        ts.fill_bytes(&mut bytes);
+        Ark(F::from_le_bytes_mod_order(&bytes))
+    }
+}
@@ -1,17 +1,73 @@
 use rand_core::{CryptoRng, RngCore};
 
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeSet, vec::Vec};
+
 pub trait Sampler: CryptoRng + RngCore {}
 
 pub trait Challenge {
     fn sample<S: CryptoRng + RngCore>(ts: &mut S) -> Self;
 }
 
+/// Sample uniformly from `[0, n)` by rejection sampling `u64`s:
+/// values below `2^64 mod n` are rejected, so the accepted range is a multiple of `n`.
+/// Rejects with probability `< n / 2^64`.
+pub(crate) fn sample_below<S: RngCore + ?Sized>(ts: &mut S, n: u64) -> u64 {
+    assert!(n > 0, "empty range");
+    let zone = n.wrapping_neg() % n;
+    loop {
+        let v = ts.next_u64();
+        if v >= zone {
+            return v % n;
+        }
+    }
+}
+
+/// How query indices are sampled by `Transcript::challenge_indices`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSampling {
+    /// Exactly `k` distinct indices (a uniform `k`-subset of the domain)
+    Distinct,
+    /// `k` independent uniform indices, duplicates are removed
+    WithReplacement,
+}
+
+/// Sample `k` indices from `[0, n)`, returned sorted and deduplicated.
+///
+/// Distinct indices are sampled using Floyd's algorithm:
+/// exactly `k` calls to `sample_below`, independent of collisions.
+#[cfg(feature = "alloc")]
+pub(crate) fn sample_indices<S: RngCore + ?Sized>(
+    ts: &mut S,
+    n: u64,
+    k: usize,
+    mode: IndexSampling,
+) -> Vec<u64> {
+    let mut set = BTreeSet::new();
+    match mode {
+        IndexSampling::Distinct => {
+            assert!(k as u64 <= n, "more distinct indices than the domain size");
+            for j in n - k as u64..n {
+                let t = sample_below(ts, j + 1);
+                if !set.insert(t) {
+                    set.insert(j);
+                }
+            }
+        }
+        IndexSampling::WithReplacement => {
+            for _ in 0..k {
+                set.insert(sample_below(ts, n));
+            }
+        }
+    }
+    set.into_iter().collect()
+}
+
 #[macro_export]
 macro_rules! challenge_int_impl {
     ( $t:tt, $n:expr ) => {
         impl Challenge for $t {
             #[inline(always)]
-            #[must_use]
             fn sample<S: CryptoRng + RngCore>(ts: &mut S) -> Self {
                 let mut buf = [0u8; $n];
                 ts.fill_bytes(&mut buf);
@@ -36,3 +92,179 @@ challenge_int_impl!(i8, 1);
 challenge_int_impl!(i16, 2);
 challenge_int_impl!(i32, 4);
 challenge_int_impl!(i64, 8);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // fixed sequence of outputs
+    struct Outputs<'a>(&'a [u64]);
+
+    impl RngCore for Outputs<'_> {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let (v, rest) = self.0.split_first().expect("out of outputs");
+            self.0 = rest;
+            *v
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    // splitmix64
+    struct SplitMix(u64);
+
+    impl RngCore for SplitMix {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sample_below_rejects() {
+        // 2^64 mod n = 2^63 - 1
+        let n = (1 << 63) + 1;
+        let mut rng = Outputs(&[0, (1 << 63) - 2, (1 << 63) - 1, 7]);
+        assert_eq!(sample_below(&mut rng, n), (1 << 63) - 1);
+        assert_eq!(rng.0, [7]);
+
+        // no rejection for powers of two
+        let mut rng = Outputs(&[0, 13]);
+        assert_eq!(sample_below(&mut rng, 8), 0);
+        assert_eq!(sample_below(&mut rng, 8), 5);
+    }
+
+    #[test]
+    fn sample_below_uniform() {
+        let mut rng = SplitMix(1);
+
+        // small range: every value within 6 standard deviations of 10000
+        let mut counts = [0u32; 6];
+        for _ in 0..60_000 {
+            counts[sample_below(&mut rng, 6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (9_400..=10_600).contains(&c)));
+
+        // large range: reducing modulo n would hit the lower half with probability 2/3
+        let n = u64::MAX / 3 * 2;
+        let low = (0..30_000)
+            .filter(|_| sample_below(&mut rng, n) < n / 2)
+            .count();
+        assert!((14_000..=16_000).contains(&low));
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn challenge_range_deterministic() {
+        use crate::{KeccakSponge, SpongeTranscript, Transcript};
+
+        let mut prover = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let mut verifier = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let msg = prover.send(42u64);
+        verifier.recv(msg);
+        for n in [1, 2, 3, 1000, u64::MAX] {
+            let c = prover.challenge_range(n);
+            assert!(c < n);
+            assert_eq!(c, verifier.challenge_range(n));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sample_indices_distinct() {
+        // collisions take the upper end of the range (Floyd's algorithm)
+        let mut rng = Outputs(&[4, 1, 6]);
+        assert_eq!(
+            sample_indices(&mut rng, 5, 3, IndexSampling::Distinct),
+            [1, 3, 4]
+        );
+
+        // exactly k sorted unique indices
+        let mut rng = SplitMix(2);
+        for (n, k) in [(1, 1), (10, 3), (10, 9), (1000, 100), (u64::MAX, 50)] {
+            let indices = sample_indices(&mut rng, n, k, IndexSampling::Distinct);
+            assert_eq!(indices.len(), k);
+            assert!(indices.windows(2).all(|w| w[0] < w[1]));
+            assert!(indices.iter().all(|&i| i < n));
+        }
+
+        // the entire domain and no indices
+        let mut rng = SplitMix(3);
+        assert_eq!(
+            sample_indices(&mut rng, 8, 8, IndexSampling::Distinct),
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert!(sample_indices(&mut rng, 8, 0, IndexSampling::Distinct).is_empty());
+        assert!(sample_indices(&mut rng, 0, 0, IndexSampling::Distinct).is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sample_indices_with_replacement() {
+        // duplicates are removed
+        let mut rng = Outputs(&[1, 1, 2]);
+        assert_eq!(
+            sample_indices(&mut rng, 4, 3, IndexSampling::WithReplacement),
+            [1, 2]
+        );
+
+        // no indices (from any domain) samples nothing
+        let mut rng = Outputs(&[]);
+        assert!(sample_indices(&mut rng, 4, 0, IndexSampling::WithReplacement).is_empty());
+        assert!(sample_indices(&mut rng, 0, 0, IndexSampling::WithReplacement).is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn sample_indices_empty_domain() {
+        sample_indices(&mut SplitMix(4), 0, 1, IndexSampling::WithReplacement);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[should_panic(expected = "more distinct indices than the domain size")]
+    fn sample_indices_too_many() {
+        sample_indices(&mut SplitMix(5), 3, 4, IndexSampling::Distinct);
+    }
+
+    #[cfg(all(feature = "keccak", feature = "alloc"))]
+    #[test]
+    fn challenge_indices_pinned() {
+        use crate::{KeccakSponge, SpongeTranscript, Transcript};
+
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        ts.append(&1u64);
+        let distinct = ts.challenge_indices(1000, 4, IndexSampling::Distinct);
+        let replaced = ts.challenge_indices(8, 4, IndexSampling::WithReplacement);
+        assert_eq!(distinct, [156, 328, 625, 675]);
+        assert_eq!(replaced, [0, 2, 4]);
+    }
+}
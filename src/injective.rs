@@ -0,0 +1,107 @@
+use crate::Absorb;
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Two distinct values with identical absorbed encodings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision<T> {
+    pub first: T,
+    pub second: T,
+    pub bytes: Vec<u8>,
+}
+
+/// Searches for distinct values with identical absorbed encodings,
+/// i.e. a witness that the `Absorb` encoding of `T` is not injective.
+///
+/// The values are typically produced by a generator, e.g.
+/// `find_collision((0..10_000).map(|_| gen(&mut rng)))`,
+/// and should cover the edge cases of the type: empty/unit values, `None`, zero-length collections.
+/// Values which cannot be absorbed (see `Absorb::try_absorb`) are skipped.
+/// Returns the first collision found or `None` if all the encodings are distinct
+/// (for distinct values).
+pub fn find_collision<T, I>(values: I) -> Option<Collision<T>>
+where
+    T: Absorb + PartialEq,
+    I: IntoIterator<Item = T>,
+{
+    let mut seen: BTreeMap<Vec<u8>, T> = BTreeMap::new();
+    for value in values {
+        let mut bytes = Vec::new();
+        if value.try_absorb(&mut bytes).is_err() {
+            continue;
+        }
+        match seen.remove(&bytes) {
+            Some(first) if first != value => {
+                return Some(Collision {
+                    first,
+                    second: value,
+                    bytes,
+                })
+            }
+            _ => {
+                seen.insert(bytes, value);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::format;
+    use alloc::string::String;
+
+    use serde::{Serialize, Serializer};
+
+    // two strings absorbed as their concatenation (not injective)
+    #[derive(Debug, PartialEq)]
+    struct Concat(String, String);
+
+    impl Serialize for Concat {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(&format_args!("{}{}", self.0, self.1))
+        }
+    }
+
+    // values failing to serialize for an empty first string
+    #[derive(Debug, PartialEq)]
+    struct Partial(String, String);
+
+    impl Serialize for Partial {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if self.0.is_empty() {
+                return Err(serde::ser::Error::custom("empty"));
+            }
+            (&self.0, &self.1).serialize(serializer)
+        }
+    }
+
+    fn pairs() -> impl Iterator<Item = (String, String)> {
+        ["", "a", "ab", "abc"]
+            .into_iter()
+            .flat_map(|x| ["", "b", "bc", "c"].map(move |y| (String::from(x), String::from(y))))
+    }
+
+    #[test]
+    fn finds_ambiguous_encoding() {
+        let collision = find_collision(pairs().map(|(x, y)| Concat(x, y))).unwrap();
+        assert_ne!(collision.first, collision.second);
+        assert_eq!(
+            format!("{}{}", collision.first.0, collision.first.1),
+            format!("{}{}", collision.second.0, collision.second.1)
+        );
+    }
+
+    #[test]
+    fn injective_encoding() {
+        assert_eq!(find_collision(pairs()), None);
+    }
+
+    #[test]
+    fn skips_unabsorbable() {
+        assert_eq!(find_collision(pairs().map(|(x, y)| Partial(x, y))), None);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::challenge::sample_below;
+use crate::Challenge;
+
+use core::ops::Deref;
+
+use rand_core::{CryptoRng, RngCore};
+
+/// Sparse ternary challenge: `N` coefficients in `{-1, 0, 1}`
+/// with exactly `W` non-zero coefficients (Hamming weight `W`).
+///
+/// Sampled like the "SampleInBall" of Dilithium:
+/// the support is a uniform `W`-subset (Fisher-Yates shuffle)
+/// and the signs are uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Ternary<const N: usize, const W: usize>(pub [i8; N]);
+
+/// Small-norm challenge: `N` coefficients uniform in `[-B, B]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Bounded<const N: usize, const B: u32>(pub [i64; N]);
+
+impl<const N: usize, const W: usize> Ternary<N, W> {
+    pub fn into_inner(self) -> [i8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize, const B: u32> Bounded<N, B> {
+    pub fn into_inner(self) -> [i64; N] {
+        self.0
+    }
+}
+
+impl<const N: usize, const W: usize> Deref for Ternary<N, W> {
+    type Target = [i8; N];
+
+    fn deref(&self) -> &[i8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize, const B: u32> Deref for Bounded<N, B> {
+    type Target = [i64; N];
+
+    fn deref(&self) -> &[i64; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize, const W: usize> Challenge for Ternary<N, W> {
+    fn sample<S: CryptoRng + RngCore>(ts: &mut S) -> Self {
+        assert!(W <= N, "weight exceeds dimension");
+
+        let mut coeffs = [0i8; N];
+        let mut signs = 0u64;
+        for (k, i) in (N - W..N).enumerate() {
+            if k % 64 == 0 {
+                signs = ts.next_u64();
+            }
+            let j = sample_below(ts, i as u64 + 1) as usize;
+            coeffs[i] = coeffs[j];
+            coeffs[j] = 1 - 2 * (signs & 1) as i8;
+            signs >>= 1;
+        }
+        Ternary(coeffs)
+    }
+}
+
+impl<const N: usize, const B: u32> Challenge for Bounded<N, B> {
+    fn sample<S: CryptoRng + RngCore>(ts: &mut S) -> Self {
+        let range = 2 * B as u64 + 1;
+        Bounded([(); N].map(|_| sample_below(ts, range) as i64 - B as i64))
+    }
+}
+
+#[cfg(all(test, feature = "keccak"))]
+mod tests {
+    use super::*;
+    use crate::{KeccakSponge, SpongeTranscript, Transcript};
+
+    fn transcript() -> SpongeTranscript<KeccakSponge> {
+        let mut ts = SpongeTranscript::new("bevis");
+        ts.append(&1u64);
+        ts
+    }
+
+    #[test]
+    fn ternary_weight() {
+        let mut ts = transcript();
+        for _ in 0..32 {
+            let c: Ternary<256, 60> = ts.challenge();
+            assert_eq!(c.iter().filter(|&&x| x != 0).count(), 60);
+            assert!(c.iter().all(|x| (-1..=1).contains(x)));
+        }
+
+        // both signs occur
+        let c: Ternary<256, 60> = ts.challenge();
+        assert!(c.contains(&1) && c.contains(&-1));
+
+        // full and empty support
+        let c: Ternary<8, 8> = ts.challenge();
+        assert!(c.iter().all(|x| x.abs() == 1));
+        let c: Ternary<8, 0> = ts.challenge();
+        assert_eq!(c.into_inner(), [0; 8]);
+    }
+
+    #[test]
+    fn bounded_range() {
+        let mut ts = transcript();
+        let mut seen = [false; 5];
+        for _ in 0..32 {
+            let c: Bounded<64, 2> = ts.challenge();
+            for &x in c.iter() {
+                assert!((-2..=2).contains(&x));
+                seen[(x + 2) as usize] = true;
+            }
+        }
+        assert_eq!(seen, [true; 5]);
+
+        let c: Bounded<8, 0> = ts.challenge();
+        assert_eq!(c.into_inner(), [0; 8]);
+    }
+
+    #[test]
+    fn pinned() {
+        let mut ts = transcript();
+        let t: Ternary<8, 3> = ts.challenge();
+        let b: Bounded<4, 1> = ts.challenge();
+        assert_eq!(t.into_inner(), [1, 0, 0, -1, 0, 0, -1, 0]);
+        assert_eq!(b.into_inner(), [0, 0, -1, 1]);
+    }
+}
@@ -1,7 +1,7 @@
 #![no_std]
 
-#[cfg(feature = "trace")]
-use alloc;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 pub use rand_core::{CryptoRng, RngCore};
 
@@ -10,6 +10,12 @@ mod challenge;
 mod msg;
 mod transcript;
 
+// sparse and small-norm challenges (lattices)
+mod lattice;
+
+// built-in sponges
+mod sponge;
+
 #[cfg(feature = "derive")]
 pub use bevis_derive::*;
 
@@ -21,23 +27,129 @@ mod trace;
 #[cfg(feature = "trace")]
 pub use trace::TraceTranscript;
 
+// declarative io patterns
+#[cfg(feature = "alloc")]
+mod pattern;
+
+// declarative io patterns
+#[cfg(feature = "alloc")]
+pub use pattern::{IoPattern, PatternTranscript};
+
+// proof streams recorded from the prover
+#[cfg(feature = "alloc")]
+mod stream;
+
+// proof streams recorded from the prover
+#[cfg(feature = "alloc")]
+pub use stream::{ProofStream, RecordingTranscript, ReplayTranscript};
+
+// injectivity checker for absorb encodings
+#[cfg(feature = "trace")]
+mod injective;
+
+// injectivity checker for absorb encodings
+#[cfg(feature = "trace")]
+pub use injective::{find_collision, Collision};
+
+// arkworks bridge
+#[cfg(feature = "ark")]
+mod ark;
+
+// arkworks bridge
+#[cfg(feature = "ark")]
+pub use ark::Ark;
+
+// zkcrypto (ff) bridge
+#[cfg(feature = "ff")]
+mod zkcrypto;
+
+// zkcrypto (ff) bridge
+#[cfg(feature = "ff")]
+pub use zkcrypto::Ff;
+
+// merlin compatible transcript
+#[cfg(feature = "merlin")]
+mod strobe;
+
+// merlin compatible transcript
+#[cfg(feature = "merlin")]
+pub use strobe::MerlinTranscript;
+
 // safe-proof interface
 #[cfg(feature = "safe")]
 mod safe;
 
 // safe-proof interface
 #[cfg(feature = "safe")]
-pub use safe::{Arthur, Bevis, Proof, Safe, SafeProof, Tx};
+pub use safe::{Arthur, Bevis, Merlin, Proof, Prove, Safe, SafeProof, Tx};
+
+// proofs carried by a proof stream
+#[cfg(all(feature = "safe", feature = "alloc"))]
+pub use safe::StreamProof;
+
+pub use absorb::{Absorb, AbsorbField, FieldHasher, FieldSampler, Hasher};
 
-pub use absorb::{Absorb, Hasher};
+pub use absorb::{AbsorbError, Reason, Segment};
 
-pub use transcript::{SpongeTranscript, Transcript};
+// errors reading proof streams
+#[cfg(feature = "alloc")]
+pub use absorb::DecodeError;
 
-pub use challenge::{Challenge, Sampler};
+// canonical packing of field elements for byte-oriented hashers
+#[cfg(feature = "ff")]
+pub use absorb::Packed;
+
+pub use transcript::{Separator, SpongeTranscript, Transcript};
+
+pub use challenge::{Challenge, IndexSampling, Sampler};
 
 pub use msg::Msg;
 
+// sparse and small-norm challenges (lattices)
+pub use lattice::{Bounded, Ternary};
+
+// tagged absorb mode
+pub use sponge::Tagged;
+
+// keccak-f[1600] duplex sponge
+#[cfg(feature = "keccak")]
+pub use sponge::KeccakSponge;
+
+// cSHAKE128/cSHAKE256 sponges
+#[cfg(feature = "shake")]
+pub use sponge::{Shake128Sponge, Shake256Sponge};
+
+// blake3 sponge
+#[cfg(feature = "blake3")]
+pub use sponge::Blake3Sponge;
+
+// poseidon sponge over a prime field
+#[cfg(feature = "poseidon")]
+pub use sponge::{PoseidonParams, PoseidonSponge};
+
 /// A "Sponge" enables both hashing and sampling (squeezing)
 pub trait Sponge: Hasher + Sampler {
     fn new(sep: &str) -> Self;
+
+    /// Absorb field elements:
+    /// byte-oriented sponges absorb the canonical byte packing (see `Packed`),
+    /// algebraic sponges over `F` absorb the elements natively.
+    #[cfg(feature = "ff")]
+    fn write_elems<F: ff::PrimeField>(&mut self, elems: &[F])
+    where
+        Self: Sized,
+    {
+        Packed(self).write_field(elems)
+    }
+
+    /// Squeeze a field element:
+    /// byte-oriented sponges sample it like `Ff<F>`,
+    /// algebraic sponges over `F` squeeze it natively.
+    #[cfg(feature = "ff")]
+    fn squeeze_elem<F: ff::PrimeField>(&mut self) -> F
+    where
+        Self: Sized,
+    {
+        Ff::<F>::sample(self).into_inner()
+    }
 }
@@ -0,0 +1,565 @@
+use crate::{
+    Absorb, AbsorbError, Challenge, DecodeError, IndexSampling, Msg, ProofStream, Sampler,
+    Separator, Transcript,
+};
+
+use alloc::vec::Vec;
+
+use core::any::type_name;
+use core::fmt;
+
+use rand_core::{CryptoRng, RngCore};
+
+use serde::de::DeserializeOwned;
+
+// domain separator for the absorbed pattern
+const PATTERN_SEP: &str = "bevis-io-pattern";
+
+// type name without leading references:
+// messages are absorbed by reference (`append(&x)`) as often as by value
+fn ty<A: ?Sized>() -> &'static str {
+    let mut name = type_name::<A>();
+    while let Some(rest) = name.strip_prefix('&') {
+        name = rest.strip_prefix("mut ").unwrap_or(rest);
+    }
+    name
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Absorb,
+    Squeeze,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Absorb => write!(f, "absorb"),
+            Kind::Squeeze => write!(f, "squeeze"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    kind: Kind,
+    count: usize,
+    label: &'static str,
+    ty: &'static str,
+}
+
+/// Declared shape of an interaction:
+/// a sequence of "absorb `n` messages of type `A`" and "squeeze `n` challenges of type `C`" steps.
+///
+/// ```ignore
+/// let pattern = IoPattern::new()
+///     .absorb::<Commitment>(1, "commitment")
+///     .squeeze::<Scalar>(2, "alpha")
+///     .absorb::<Response>(1, "response");
+/// let mut ts = SpongeTranscript::<KeccakSponge>::with_pattern("my-protocol", pattern);
+/// ```
+///
+/// The kinds, counts and labels of the steps are absorbed when the transcript is created,
+/// the types are only checked at runtime (type names are not stable across compilers).
+///
+/// Types are compared by name, ignoring references: `absorb::<u64>` matches `append(&&1u64)`,
+/// `absorb::<str>` matches `append(&"abc")` but not `append(&String::new())`.
+/// `Bevis::prove` / `Bevis::verify` absorb `Proof::NAME` (a `str`) and the statement
+/// before running the protocol, hence the pattern of a proof starts with these two steps.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IoPattern {
+    steps: Vec<Step>,
+}
+
+impl IoPattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect `count` messages of type `A` (via `append`, `recv` or `send`)
+    pub fn absorb<A: ?Sized>(self, count: usize, label: &'static str) -> Self {
+        self.step(Kind::Absorb, count, label, ty::<A>())
+    }
+
+    /// Expect `count` challenges of type `C` (via `challenge` and friends)
+    ///
+    /// Reading bytes directly from the transcript (`RngCore`) squeezes one `u8` per byte.
+    pub fn squeeze<C>(self, count: usize, label: &'static str) -> Self {
+        self.step(Kind::Squeeze, count, label, ty::<C>())
+    }
+
+    fn step(mut self, kind: Kind, count: usize, label: &'static str, ty: &'static str) -> Self {
+        assert!(count > 0, "empty io pattern step");
+        self.steps.push(Step {
+            kind,
+            count,
+            label,
+            ty,
+        });
+        self
+    }
+}
+
+/// Transcript wrapper which enforces an `IoPattern`.
+///
+/// Panics as soon as a call deviates from the declared pattern,
+/// use `finish` to check that the pattern has been completed.
+///
+/// Each call counts as one unit of its type, except
+/// `challenge_fill` / `challenge_vec` (one unit of `C` per element),
+/// `challenge_range` (one `u64`), `challenge_indices` (one `Vec<u64>`),
+/// `append_bytes_labeled` (one `[u8]`)
+/// and `challenge_bytes` / direct `RngCore` reads (one `u8` per byte).
+/// `grind` (as well as `verify_grind` and `read_grind`) is one `u64` absorbed (the nonce).
+///
+/// Labelled calls (`append_labeled`, `challenge_labeled`, ...) must match the label of the step,
+/// unlabelled calls match any label.
+///
+/// Children of `fork` run a sub-interaction which is not part of the pattern,
+/// hence they are not checked (nor is the pattern advanced by them).
+#[derive(Debug, Clone)]
+pub struct PatternTranscript<T: Transcript> {
+    ts: T,
+    steps: Vec<Step>,
+    pos: usize,    // current step
+    done: usize,   // units of the current step consumed
+    checked: bool, // false for children of `fork`
+}
+
+impl<T: Transcript> PatternTranscript<T> {
+    /// Absorbs the pattern into `ts` and starts enforcing it
+    pub fn new(mut ts: T, pattern: IoPattern) -> Self {
+        let encoded: Vec<(u8, u64, &str)> = pattern
+            .steps
+            .iter()
+            .map(|s| (s.kind as u8, s.count as u64, s.label))
+            .collect();
+        ts.append(&(PATTERN_SEP, encoded));
+        Self {
+            ts,
+            steps: pattern.steps,
+            pos: 0,
+            done: 0,
+            checked: true,
+        }
+    }
+
+    /// Checks that the pattern is complete and returns the inner transcript
+    pub fn finish(self) -> T {
+        if let Some(step) = self.steps.get(self.pos).filter(|_| self.checked) {
+            panic!(
+                "io pattern incomplete: {} {} of {} {} ({}) remaining",
+                step.kind,
+                step.count - self.done,
+                step.count,
+                step.ty,
+                step.label
+            );
+        }
+        self.ts
+    }
+
+    // labelled calls must match the label of the step, unlabelled calls match any label
+    fn check(&mut self, kind: Kind, ty: &'static str, mut count: usize, label: Option<&str>) {
+        while self.checked && count > 0 {
+            let step = match self.steps.get(self.pos) {
+                Some(step) => step,
+                None => panic!(
+                    "io pattern violated: {} {} after the end of the pattern",
+                    kind, ty
+                ),
+            };
+            if step.kind != kind || step.ty != ty {
+                panic!(
+                    "io pattern violated: expected {} {} ({}), got {} {}",
+                    step.kind, step.ty, step.label, kind, ty
+                );
+            }
+            if let Some(label) = label.filter(|label| *label != step.label) {
+                panic!(
+                    "io pattern violated: expected {} {} ({}), got {} {} ({})",
+                    step.kind, step.ty, step.label, kind, ty, label
+                );
+            }
+            let take = count.min(step.count - self.done);
+            count -= take;
+            self.done += take;
+            if self.done == step.count {
+                self.pos += 1;
+                self.done = 0;
+            }
+        }
+    }
+}
+
+impl<T: Transcript> RngCore for PatternTranscript<T> {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.check(Kind::Squeeze, ty::<u8>(), dest.len(), None);
+        self.ts.fill_bytes(dest)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.check(Kind::Squeeze, ty::<u8>(), 4, None);
+        self.ts.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.check(Kind::Squeeze, ty::<u8>(), 8, None);
+        self.ts.next_u64()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.check(Kind::Squeeze, ty::<u8>(), dest.len(), None);
+        self.ts.try_fill_bytes(dest)
+    }
+}
+
+impl<T: Transcript> CryptoRng for PatternTranscript<T> {}
+
+impl<T: Transcript> Sampler for PatternTranscript<T> {}
+
+impl<T: Transcript> Transcript for PatternTranscript<T> {
+    fn append<A: Absorb>(&mut self, elem: &A) {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.append(elem)
+    }
+
+    fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.try_append(elem)
+    }
+
+    fn challenge<C: Challenge>(&mut self) -> C {
+        self.check(Kind::Squeeze, ty::<C>(), 1, None);
+        self.ts.challenge()
+    }
+
+    /// Stops checking in the child of a `fork`
+    /// (also when forking a wrapper, e.g. `Merlin`, which forwards the separator)
+    fn separate(&mut self, sep: Separator, label: &str) {
+        if sep == Separator::ForkChild {
+            self.checked = false;
+        }
+        self.ts.separate(sep, label)
+    }
+
+    fn recv<A: Absorb>(&mut self, msg: Msg<A>) -> A {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.recv(msg)
+    }
+
+    fn send<A: Absorb>(&mut self, elem: A) -> Msg<A> {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.send(elem)
+    }
+
+    fn try_recv<A: Absorb>(&mut self, msg: Msg<A>) -> Result<A, AbsorbError> {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.try_recv(msg)
+    }
+
+    fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+        self.check(Kind::Absorb, ty::<A>(), 1, Some(label));
+        self.ts.append_labeled(label, elem)
+    }
+
+    fn recv_labeled<A: Absorb>(&mut self, label: &'static str, msg: Msg<A>) -> A {
+        self.check(Kind::Absorb, ty::<A>(), 1, Some(label));
+        self.ts.recv_labeled(label, msg)
+    }
+
+    fn send_labeled<A: Absorb>(&mut self, label: &'static str, elem: A) -> Msg<A> {
+        self.check(Kind::Absorb, ty::<A>(), 1, Some(label));
+        self.ts.send_labeled(label, elem)
+    }
+
+    fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+        self.check(Kind::Squeeze, ty::<C>(), 1, Some(label));
+        self.ts.challenge_labeled(label)
+    }
+
+    fn challenge_bytes(&mut self, dest: &mut [u8]) {
+        self.check(Kind::Squeeze, ty::<u8>(), dest.len(), None);
+        self.ts.challenge_bytes(dest)
+    }
+
+    fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+        self.check(Kind::Squeeze, ty::<u8>(), dest.len(), Some(label));
+        self.ts.challenge_bytes_labeled(label, dest)
+    }
+
+    fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+        self.check(Kind::Absorb, ty::<&[u8]>(), 1, Some(label));
+        self.ts.append_bytes_labeled(label, bytes)
+    }
+
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.append_field(elem)
+    }
+
+    #[cfg(feature = "ff")]
+    fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+        self.check(Kind::Squeeze, ty::<F>(), 1, None);
+        self.ts.challenge_field()
+    }
+
+    fn challenge_fill<C: Challenge>(&mut self, dest: &mut [C]) {
+        self.check(Kind::Squeeze, ty::<C>(), dest.len(), None);
+        self.ts.challenge_fill(dest)
+    }
+
+    fn challenge_vec<C: Challenge>(&mut self, n: usize) -> Vec<C> {
+        self.check(Kind::Squeeze, ty::<C>(), n, None);
+        self.ts.challenge_vec(n)
+    }
+
+    fn challenge_range(&mut self, n: u64) -> u64 {
+        self.check(Kind::Squeeze, ty::<u64>(), 1, None);
+        self.ts.challenge_range(n)
+    }
+
+    fn challenge_indices(&mut self, n: u64, k: usize, mode: IndexSampling) -> Vec<u64> {
+        self.check(Kind::Squeeze, ty::<Vec<u64>>(), 1, None);
+        self.ts.challenge_indices(n, k, mode)
+    }
+}
+
+impl<T: ProofStream> ProofStream for PatternTranscript<T> {
+    fn read<A: Absorb + DeserializeOwned>(&mut self) -> Result<A, DecodeError> {
+        self.check(Kind::Absorb, ty::<A>(), 1, None);
+        self.ts.read()
+    }
+
+    fn read_labeled<A: Absorb + DeserializeOwned>(
+        &mut self,
+        label: &'static str,
+    ) -> Result<A, DecodeError> {
+        self.check(Kind::Absorb, ty::<A>(), 1, Some(label));
+        self.ts.read_labeled(label)
+    }
+}
+
+#[cfg(all(test, feature = "keccak"))]
+mod tests {
+    use super::*;
+    use crate::{KeccakSponge, SpongeTranscript};
+
+    fn pattern() -> IoPattern {
+        IoPattern::new()
+            .absorb::<u64>(1, "a")
+            .absorb::<u64>(1, "b")
+            .squeeze::<u64>(2, "c")
+    }
+
+    fn transcript(pattern: IoPattern) -> PatternTranscript<SpongeTranscript<KeccakSponge>> {
+        SpongeTranscript::with_pattern("bevis", pattern)
+    }
+
+    #[test]
+    fn follows_pattern() {
+        let mut ts = transcript(pattern());
+        ts.append_labeled("a", &1u64);
+        ts.recv_labeled("b", Msg(2u64));
+        let c: u64 = ts.challenge_labeled("c");
+        let _: u64 = ts.challenge();
+        ts.finish();
+
+        // the pattern is absorbed
+        let mut other = transcript(pattern().absorb::<u8>(1, "d"));
+        other.append_labeled("a", &1u64);
+        other.recv_labeled("b", Msg(2u64));
+        assert_ne!(other.challenge_labeled::<u64>("c"), c);
+
+        // unlabelled calls match any label
+        let mut ts = transcript(pattern());
+        ts.append(&1u64);
+        ts.append(&2u64);
+        ts.challenge_fill(&mut [0u64; 2]);
+        ts.finish();
+    }
+
+    #[test]
+    #[should_panic(expected = "io pattern violated: expected absorb u64 (a), got absorb u64 (b)")]
+    fn swapped_labels() {
+        let mut ts = transcript(pattern());
+        ts.append_labeled("b", &2u64);
+        ts.append_labeled("a", &1u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "io pattern violated: expected squeeze u64 (c), got absorb u64")]
+    fn wrong_kind() {
+        let mut ts = transcript(pattern());
+        ts.append(&1u64);
+        ts.append(&2u64);
+        ts.append(&3u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "io pattern violated: expected absorb u64 (a), got absorb u32")]
+    fn wrong_type() {
+        let mut ts = transcript(pattern());
+        ts.append(&1u32);
+    }
+
+    #[test]
+    fn references() {
+        let pattern = IoPattern::new()
+            .absorb::<u64>(1, "a")
+            .absorb::<&u64>(1, "b")
+            .absorb::<str>(1, "c")
+            .absorb::<[u8]>(1, "d");
+        let mut ts = transcript(pattern);
+        ts.append(&&1u64);
+        ts.append(&2u64);
+        ts.append(&"abc");
+        ts.append_bytes_labeled("d", b"abc");
+        ts.finish();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "io pattern violated: expected absorb str (c), got absorb alloc::string::String"
+    )]
+    fn owned_string() {
+        let mut ts = transcript(IoPattern::new().absorb::<str>(1, "c"));
+        ts.append(&alloc::string::String::from("abc"));
+    }
+
+    #[cfg(feature = "safe")]
+    #[test]
+    fn bevis() {
+        use crate::{Bevis, CryptoRng, Proof, Prove, Safe, Sponge};
+
+        // proof of knowledge of `x` with `x * x = st`, after a proof-of-work
+        struct Root {
+            pow: Msg<u64>,
+            x: Msg<u64>,
+        }
+
+        impl Proof for Root {
+            type CRS = ();
+            type Error = ();
+            type Result = ();
+            type Statement = u64;
+
+            const NAME: &'static str = "root";
+
+            fn consume<T: Transcript + Safe>(
+                self,
+                _crs: &(),
+                st: &u64,
+                ts: &mut T,
+            ) -> Result<(), ()> {
+                let pow = ts.verify_grind(2, self.pow);
+                let x = ts.recv_labeled("x", self.x);
+                match pow && x.checked_mul(x) == Some(*st) {
+                    true => Ok(()),
+                    false => Err(()),
+                }
+            }
+        }
+
+        impl Prove for Root {
+            type Witness = u64;
+
+            fn create<T: Transcript + Safe, R: RngCore + CryptoRng>(
+                _crs: &(),
+                _st: &u64,
+                wit: &u64,
+                _rng: &mut R,
+                ts: &mut T,
+            ) -> Result<Self, ()> {
+                let pow = ts.grind(2);
+                let x = ts.send_labeled("x", *wit);
+                Ok(Root { pow, x })
+            }
+        }
+
+        // the name and the statement, then the protocol
+        let pattern = || {
+            IoPattern::new()
+                .absorb::<str>(1, "name")
+                .absorb::<u64>(1, "statement")
+                .absorb::<u64>(1, "pow")
+                .absorb::<u64>(1, "x")
+                .squeeze::<u64>(1, "c")
+        };
+
+        let mut rng = KeccakSponge::<136>::new("rng");
+        let mut prover = transcript(pattern());
+        let pf: Root = prover.prove(&(), &9, &3, &mut rng).unwrap();
+        let c: u64 = prover.challenge();
+        prover.finish();
+
+        let mut verifier = transcript(pattern());
+        verifier.verify(&(), &9, pf).unwrap();
+        assert_eq!(verifier.challenge::<u64>(), c);
+        verifier.finish();
+    }
+
+    #[test]
+    #[should_panic(expected = "after the end of the pattern")]
+    fn past_the_end() {
+        let mut ts = transcript(IoPattern::new().absorb::<u64>(1, "a"));
+        ts.append(&1u64);
+        let _: u64 = ts.challenge();
+    }
+
+    #[test]
+    #[should_panic(expected = "io pattern incomplete: squeeze 1 of 2 u64 (c) remaining")]
+    fn incomplete() {
+        let mut ts = transcript(pattern());
+        ts.append(&1u64);
+        ts.append(&2u64);
+        let _: u64 = ts.challenge();
+        ts.finish();
+    }
+
+    #[test]
+    fn grind() {
+        let pattern = || {
+            IoPattern::new()
+                .absorb::<u64>(1, "nonce")
+                .squeeze::<u64>(1, "c")
+        };
+        let mut prover = transcript(pattern());
+        let nonce = prover.grind(4);
+        let c: u64 = prover.challenge();
+        prover.finish();
+
+        let mut verifier = transcript(pattern());
+        assert!(verifier.verify_grind(4, Msg(nonce.0)));
+        assert_eq!(verifier.challenge::<u64>(), c);
+        verifier.finish();
+
+        // the pattern does not change the transcript (besides absorbing the pattern)
+        let mut direct = SpongeTranscript::<KeccakSponge>::new("bevis");
+        direct.append(&(
+            PATTERN_SEP,
+            alloc::vec![(0u8, 1u64, "nonce"), (1u8, 1u64, "c")],
+        ));
+        assert_eq!(direct.grind(4).0, nonce.0);
+        assert_eq!(direct.challenge::<u64>(), c);
+
+        // nor are children of a fork checked
+        let mut ts = transcript(pattern());
+        let mut child = ts.fork("sub");
+        child.append(&1u8);
+        let _: [u8; 3] = child.challenge();
+        child.finish();
+
+        // also when forking through a wrapper
+        #[cfg(feature = "safe")]
+        {
+            let mut child = crate::Merlin::new(&mut ts).fork("sub");
+            child.append(&1u8);
+        }
+
+        ts.append(&1u64);
+        let _: u64 = ts.challenge();
+        ts.finish();
+    }
+}
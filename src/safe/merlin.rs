@@ -0,0 +1,18 @@
+use super::Inner;
+use crate::Transcript;
+
+/// Prover-side counterpart of `Arthur`:
+/// a transcript to which the statement has been bound by `Bevis::prove`.
+///
+/// Named after the prover of Arthur-Merlin protocols,
+/// unrelated to the Merlin transcript construction (see `bevis::MerlinTranscript`).
+#[repr(transparent)]
+pub struct Merlin<'a, T: Transcript>(Inner<'a, T>);
+
+impl<'a, T: Transcript> Merlin<'a, T> {
+    pub(crate) fn new(tx: &'a mut T) -> Self {
+        Merlin(Inner::Borrowed(tx))
+    }
+}
+
+safe_transcript_impl!(Merlin);
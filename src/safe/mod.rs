@@ -1,16 +1,223 @@
+// Implements `Transcript` (and `Safe`) for a wrapper around `Inner`
+// by forwarding every method to the wrapped transcript,
+// such that the wrapper behaves exactly as the transcript it is bound to.
+macro_rules! safe_transcript_impl {
+    ($name:ident) => {
+        // scope the imports to the generated impls
+        const _: () = {
+            use super::{Inner, Sealed};
+            use crate::{
+                Absorb, AbsorbError, Challenge, Msg, Safe, Sampler, Separator, Transcript,
+            };
+
+            use rand_core::{CryptoRng, RngCore};
+
+            impl<'a, T: Transcript> CryptoRng for $name<'a, T> {}
+
+            impl<'a, T: Transcript> RngCore for $name<'a, T> {
+                #[inline(always)]
+                fn fill_bytes(&mut self, dest: &mut [u8]) {
+                    self.0.fill_bytes(dest)
+                }
+
+                #[inline(always)]
+                fn next_u32(&mut self) -> u32 {
+                    self.0.next_u32()
+                }
+
+                #[inline(always)]
+                fn next_u64(&mut self) -> u64 {
+                    self.0.next_u64()
+                }
+
+                #[inline(always)]
+                fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                    self.0.try_fill_bytes(dest)
+                }
+            }
+
+            impl<'a, T: Transcript> Sampler for $name<'a, T> {}
+
+            impl<'a, T: Transcript> Sealed for $name<'a, T> {}
+
+            impl<'a, T: Transcript + Clone> Safe for $name<'a, T> {}
+
+            /// The clone owns a copy of the transcript (e.g. the child of a `fork`)
+            impl<'a, T: Transcript + Clone> Clone for $name<'a, T> {
+                fn clone(&self) -> Self {
+                    $name(Inner::Owned(T::clone(&self.0)))
+                }
+            }
+
+            impl<'a, T: Transcript> Transcript for $name<'a, T> {
+                #[inline(always)]
+                fn append<A: Absorb>(&mut self, elem: &A) {
+                    self.0.append(elem)
+                }
+
+                #[inline(always)]
+                fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+                    self.0.try_append(elem)
+                }
+
+                #[inline(always)]
+                fn challenge<C: Challenge>(&mut self) -> C {
+                    self.0.challenge()
+                }
+
+                #[inline(always)]
+                fn separate(&mut self, sep: Separator, label: &str) {
+                    self.0.separate(sep, label)
+                }
+
+                #[inline(always)]
+                fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+                    self.0.append_labeled(label, elem)
+                }
+
+                #[inline(always)]
+                fn recv_labeled<A: Absorb>(&mut self, label: &'static str, msg: Msg<A>) -> A {
+                    self.0.recv_labeled(label, msg)
+                }
+
+                #[inline(always)]
+                fn send_labeled<A: Absorb>(&mut self, label: &'static str, elem: A) -> Msg<A> {
+                    self.0.send_labeled(label, elem)
+                }
+
+                #[inline(always)]
+                fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+                    self.0.challenge_labeled(label)
+                }
+
+                #[inline(always)]
+                fn challenge_bytes(&mut self, dest: &mut [u8]) {
+                    self.0.challenge_bytes(dest)
+                }
+
+                #[inline(always)]
+                fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+                    self.0.challenge_bytes_labeled(label, dest)
+                }
+
+                #[inline(always)]
+                fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+                    self.0.append_bytes_labeled(label, bytes)
+                }
+
+                #[cfg(feature = "ff")]
+                #[inline(always)]
+                fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+                    self.0.append_field(elem)
+                }
+
+                #[cfg(feature = "ff")]
+                #[inline(always)]
+                fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+                    self.0.challenge_field()
+                }
+
+                #[inline(always)]
+                fn challenge_fill<C: Challenge>(&mut self, dest: &mut [C]) {
+                    self.0.challenge_fill(dest)
+                }
+
+                #[cfg(feature = "alloc")]
+                #[inline(always)]
+                fn challenge_vec<C: Challenge>(&mut self, n: usize) -> alloc::vec::Vec<C> {
+                    self.0.challenge_vec(n)
+                }
+
+                #[inline(always)]
+                fn challenge_range(&mut self, n: u64) -> u64 {
+                    self.0.challenge_range(n)
+                }
+
+                #[cfg(feature = "alloc")]
+                #[inline(always)]
+                fn challenge_indices(
+                    &mut self,
+                    n: u64,
+                    k: usize,
+                    mode: crate::IndexSampling,
+                ) -> alloc::vec::Vec<u64> {
+                    self.0.challenge_indices(n, k, mode)
+                }
+
+                #[inline(always)]
+                fn recv<A: Absorb>(&mut self, msg: Msg<A>) -> A {
+                    self.0.recv(msg)
+                }
+
+                #[inline(always)]
+                fn send<A: Absorb>(&mut self, elem: A) -> Msg<A> {
+                    self.0.send(elem)
+                }
+
+                #[inline(always)]
+                fn try_recv<A: Absorb>(&mut self, msg: Msg<A>) -> Result<A, AbsorbError> {
+                    self.0.try_recv(msg)
+                }
+            }
+        };
+    };
+}
+
 mod arthur;
+mod merlin;
 mod proof;
 
 pub use arthur::Arthur;
-pub use proof::{Bevis, Proof, SafeProof};
+pub use merlin::Merlin;
+pub use proof::{Bevis, Proof, Prove, SafeProof};
+
+#[cfg(feature = "alloc")]
+pub use proof::StreamProof;
 
 use core::fmt::Debug;
+use core::ops::{Deref, DerefMut};
 
 use crate::Msg;
 
+// transcript of `Arthur` / `Merlin`:
+// borrowed from `Bevis::verify` / `Bevis::prove` or owned by a clone (e.g. a fork)
+enum Inner<'a, T> {
+    Borrowed(&'a mut T),
+    Owned(T),
+}
+
+impl<T> Deref for Inner<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        match self {
+            Inner::Borrowed(ts) => ts,
+            Inner::Owned(ts) => ts,
+        }
+    }
+}
+
+impl<T> DerefMut for Inner<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            Inner::Borrowed(ts) => ts,
+            Inner::Owned(ts) => ts,
+        }
+    }
+}
+
 /// Guarantees that the transcript occurs only in a
 /// context in which it is bound to the statement.
-pub trait Safe: arthur::Sealed {}
+///
+/// Safe transcripts are `Clone` (owning a copy of the transcript),
+/// such that protocols can `fork` them.
+pub trait Safe: Sealed + Clone {}
+
+/// Implemented only by `Arthur` and `Merlin`,
+/// such that `Safe` cannot be implemented outside this crate.
+pub trait Sealed {}
 
 /// Marker trait.
 ///
@@ -1,4 +1,4 @@
-use crate::{Absorb, Arthur, CryptoRng, RngCore, Safe, Transcript, Tx};
+use crate::{Absorb, Arthur, CryptoRng, Merlin, RngCore, Safe, Transcript, Tx};
 
 /// A safe proof is a proof where Fiat-Shamir is
 /// guaranteed to be implemented correctly:
@@ -9,7 +9,6 @@ pub trait Proof: Sized {
     type CRS;
     type Error;
     type Result;
-    type Witness;
     type Statement;
 
     /// Every protocol should have a unique identifier.
@@ -39,27 +38,65 @@ pub trait Proof: Sized {
         st: &Self::Statement, // statement
         ts: &mut T,           // transcript
     ) -> Result<Self::Result, Self::Error>;
+}
 
-    /// This method added to simplify composition of proofs:
-    /// allowing the recursive invocation similar to .verify
-    /// when creating proofs with sub-protocols
-    /// (e.g. compressed sigma protocols / folding arguments)
+/// The prover of a proof system,
+/// separate from `Proof` such that verifier-only proofs need no prover.
+pub trait Prove: Proof {
+    type Witness;
+
+    /// You CANNOT invoke this method directly instead you must use sponge.prove.
+    /// This is done to ensure that the statement is committed to,
+    /// exactly as in "consume".
     ///
-    /// A default implementation is provided,
-    /// in case the user wants to produce proofs in some other way.
-    #[allow(unused_variables)]
-    fn create<T: Transcript, R: RngCore + CryptoRng>(
+    /// However, you MAY recursively invoke "create" method from other
+    /// "create" methods (since you have a Merlin instance),
+    /// allowing the composition of provers for sub-protocols
+    /// (e.g. compressed sigma protocols / folding arguments)
+    fn create<T: Transcript + Safe, R: RngCore + CryptoRng>(
         crs: &Self::CRS,      // common reference string (constant)
         st: &Self::Statement, // statement
         wit: &Self::Witness,  // witness
         rng: &mut R,          // sampling of randomness
         ts: &mut T,           // transcript
-    ) -> Result<Self, Self::Error> {
-        todo!()
-    }
+    ) -> Result<Self, Self::Error>;
 }
 
-pub trait Bevis: Transcript {
+/// A proof carried by a proof stream (see `RecordingTranscript` / `ReplayTranscript`):
+/// the prover sends its messages into the stream and the verifier reads them back,
+/// hence there is no proof struct.
+///
+/// Proven by `RecordingTranscript::prove_stream` and verified by `ReplayTranscript::verify_stream`,
+/// which bind the statement exactly as `Bevis::prove` / `Bevis::verify`.
+#[cfg(feature = "alloc")]
+pub trait StreamProof {
+    type CRS;
+    type Error;
+    type Result;
+    type Statement;
+    type Witness;
+
+    /// Unique identifier of the protocol (see `Proof::NAME`)
+    const NAME: &'static str;
+
+    /// As `Proof::consume`, reading the messages of the prover from the proof stream.
+    fn consume<T: crate::ProofStream + Safe>(
+        crs: &Self::CRS,      // this MUST be a fixed value.
+        st: &Self::Statement, // statement
+        ts: &mut T,           // transcript
+    ) -> Result<Self::Result, Self::Error>;
+
+    /// As `Prove::create`, sending the messages into the proof stream.
+    fn create<T: Transcript + Safe, R: RngCore + CryptoRng>(
+        crs: &Self::CRS,      // common reference string (constant)
+        st: &Self::Statement, // statement
+        wit: &Self::Witness,  // witness
+        rng: &mut R,          // sampling of randomness
+        ts: &mut T,           // transcript
+    ) -> Result<(), Self::Error>;
+}
+
+pub trait Bevis: Transcript + Clone {
     /// In-order to verify a statement it must be absorbable,
     /// note that sub-protocols do not need absorable statements.
     ///
@@ -87,7 +124,9 @@ pub trait Bevis: Transcript {
 
     /// Provide for convience:
     /// makes it easier to compose the prover for different sub-protocols
-    fn prove<R: RngCore + CryptoRng, P: Proof>(
+    ///
+    /// This method cannot be overwritten since Merlin has no public constructor.
+    fn prove<R: RngCore + CryptoRng, P: Prove>(
         &mut self,
         crs: &P::CRS,      // common reference string (constant)
         st: &P::Statement, // statement
@@ -104,8 +143,66 @@ pub trait Bevis: Transcript {
         self.append(st);
 
         // run the prover to obtain the proof
-        P::create(crs, st, wit, rng, self)
+        P::create(crs, st, wit, rng, &mut Merlin::new(self))
     }
 }
 
-impl<T: Transcript> Bevis for T {}
+impl<T: Transcript + Clone> Bevis for T {}
+
+#[cfg(all(test, feature = "keccak"))]
+mod tests {
+    use super::*;
+    use crate::{KeccakSponge, Msg, Sponge, SpongeTranscript};
+
+    // proof of knowledge of `x` with `x * x = st` (revealing `x`)
+    struct Root {
+        x: Msg<u64>,
+    }
+
+    impl Proof for Root {
+        type CRS = ();
+        type Error = ();
+        type Result = ();
+        type Statement = u64;
+
+        const NAME: &'static str = "root";
+
+        fn consume<T: Transcript + Safe>(self, _crs: &(), st: &u64, ts: &mut T) -> Result<(), ()> {
+            let x = ts.recv(self.x);
+            match x.checked_mul(x) == Some(*st) {
+                true => Ok(()),
+                false => Err(()),
+            }
+        }
+    }
+
+    // the prover is the only one naming a witness
+    impl Prove for Root {
+        type Witness = u64;
+
+        fn create<T: Transcript + Safe, R: RngCore + CryptoRng>(
+            _crs: &(),
+            _st: &u64,
+            wit: &u64,
+            _rng: &mut R,
+            ts: &mut T,
+        ) -> Result<Self, ()> {
+            Ok(Root { x: ts.send(*wit) })
+        }
+    }
+
+    #[test]
+    fn prove_verify() {
+        let mut prover = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let mut rng = KeccakSponge::<136>::new("rng");
+        let pf: Root = prover.prove(&(), &9, &3, &mut rng).unwrap();
+
+        let mut verifier = SpongeTranscript::<KeccakSponge>::new("bevis");
+        verifier.verify(&(), &9, pf).unwrap();
+        assert_eq!(prover.challenge::<u64>(), verifier.challenge::<u64>());
+
+        let mut verifier = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let pf = Root { x: Msg(4) };
+        assert!(verifier.verify(&(), &9, pf).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{Hasher, Sampler, Sponge};
+
+use rand_core::{impls, CryptoRng, RngCore};
+
+/// Sponge based on the BLAKE3 extendable output function.
+///
+/// - The separator is used as the BLAKE3 key derivation context,
+///   i.e. the sponge starts as `Hasher::new_derive_key(sep)`.
+/// - Squeezing finalizes the hasher and reads from its output stream.
+/// - Writing after squeezing ratchets: a 32 byte key is read from the output stream
+///   and absorption continues in a fresh keyed BLAKE3 instance.
+#[derive(Debug, Clone)]
+pub struct Blake3Sponge {
+    hasher: blake3::Hasher,
+    reader: Option<blake3::OutputReader>,
+}
+
+impl Sponge for Blake3Sponge {
+    fn new(sep: &str) -> Self {
+        Self {
+            hasher: blake3::Hasher::new_derive_key(sep),
+            reader: None,
+        }
+    }
+}
+
+impl Hasher for Blake3Sponge {
+    fn write(&mut self, buf: &[u8]) {
+        if let Some(mut reader) = self.reader.take() {
+            let mut key = [0u8; blake3::KEY_LEN];
+            reader.fill(&mut key);
+            self.hasher = blake3::Hasher::new_keyed(&key);
+        }
+        self.hasher.update(buf);
+    }
+}
+
+impl RngCore for Blake3Sponge {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.reader
+            .get_or_insert_with(|| self.hasher.finalize_xof())
+            .fill(dest)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Blake3Sponge {}
+
+impl Sampler for Blake3Sponge {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector() {
+        let mut sponge = Blake3Sponge::new("bevis");
+        sponge.write(b"abc");
+        let mut out = [0u8; 32];
+        sponge.fill_bytes(&mut out);
+        assert_eq!(
+            out,
+            [
+                0x7e, 0xfd, 0x11, 0x97, 0x1c, 0x27, 0x44, 0xd1, 0xa9, 0x15, 0xc5, 0x4e, 0x99, 0xa3,
+                0xf6, 0x57, 0x28, 0x36, 0xa9, 0x06, 0x1b, 0x43, 0xee, 0x06, 0x7c, 0x3a, 0xf6, 0x17,
+                0xd6, 0xaf, 0x1c, 0xbd
+            ]
+        );
+
+        // the key derivation function of BLAKE3 with the separator as context
+        assert_eq!(out, blake3::derive_key("bevis", b"abc"));
+    }
+
+    #[test]
+    fn rekey() {
+        // continue a transcript after squeezing
+        let mut sponge = Blake3Sponge::new("bevis");
+        sponge.write(b"x");
+        let mut challenge = [0u8; 16];
+        sponge.fill_bytes(&mut challenge);
+
+        // the key read from the output stream
+        let mut key = [0u8; blake3::KEY_LEN];
+        sponge.clone().fill_bytes(&mut key);
+
+        sponge.write(b"y");
+        let mut continued = [0u8; 32];
+        sponge.fill_bytes(&mut continued);
+
+        // a keyed instance absorbing the remaining bytes
+        assert_eq!(continued, *blake3::keyed_hash(&key, b"y").as_bytes());
+
+        // which differs from a fresh transcript absorbing the same bytes
+        let mut fresh = Blake3Sponge::new("bevis");
+        fresh.write(&key);
+        fresh.write(b"y");
+        let mut out = [0u8; 32];
+        fresh.fill_bytes(&mut out);
+        assert_ne!(continued, out);
+    }
+}
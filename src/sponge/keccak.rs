@@ -0,0 +1,164 @@
+use crate::{Hasher, Sampler, Sponge};
+
+use rand_core::{impls, CryptoRng, RngCore};
+
+const WIDTH: usize = 200; // width of Keccak-f[1600] in bytes
+
+// domain bytes used when padding the rate before a permutation
+const DOMAIN_INIT: u8 = 0x02;
+const DOMAIN_SQUEEZE: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Absorb,
+    Squeeze,
+}
+
+/// Duplex sponge over the Keccak-f[1600] permutation.
+///
+/// The rate is given in bytes (default 136, i.e. capacity 512 bits),
+/// and must be non-zero and strictly less than the 200 byte state.
+///
+/// - The separator is absorbed as `len(sep) as u64 (LE) || sep`,
+///   after which the rate is padded and the state permuted.
+/// - Switching from absorbing to squeezing pads the rate and permutes.
+/// - Switching from squeezing to absorbing permutes,
+///   such that squeezed output can never be recovered from the state.
+///
+/// Padding XORs a domain byte at the current position and 0x80 at the last byte of the rate.
+///
+/// # Test vector
+///
+/// With the default rate, `KeccakSponge::new("bevis")` followed by writing `b"abc"`
+/// and squeezing 32 bytes yields:
+///
+/// ```text
+/// c84f78d3061e66b0072b3c918966fcc16ad233ad9eb8a1f14d9456cc6bf45e80
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeccakSponge<const RATE: usize = 136> {
+    state: [u64; 25],
+    pos: usize,
+    mode: Mode,
+}
+
+impl<const RATE: usize> KeccakSponge<RATE> {
+    const VALID_RATE: () = assert!(RATE > 0 && RATE < WIDTH, "invalid rate");
+
+    /// Capacity of the sponge in bytes.
+    pub const CAPACITY: usize = WIDTH - RATE;
+
+    #[inline(always)]
+    fn xor_byte(&mut self, i: usize, v: u8) {
+        self.state[i / 8] ^= (v as u64) << (8 * (i % 8));
+    }
+
+    #[inline(always)]
+    fn get_byte(&self, i: usize) -> u8 {
+        (self.state[i / 8] >> (8 * (i % 8))) as u8
+    }
+
+    fn permute(&mut self) {
+        keccak::f1600(&mut self.state);
+        self.pos = 0;
+    }
+
+    fn pad_permute(&mut self, domain: u8) {
+        if self.pos == RATE {
+            self.permute();
+        }
+        self.xor_byte(self.pos, domain);
+        self.xor_byte(RATE - 1, 0x80);
+        self.permute();
+    }
+
+    fn absorb_bytes(&mut self, buf: &[u8]) {
+        for &v in buf {
+            if self.pos == RATE {
+                self.permute();
+            }
+            self.xor_byte(self.pos, v);
+            self.pos += 1;
+        }
+    }
+}
+
+impl<const RATE: usize> Sponge for KeccakSponge<RATE> {
+    fn new(sep: &str) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::VALID_RATE;
+        let mut sponge = Self {
+            state: [0; 25],
+            pos: 0,
+            mode: Mode::Absorb,
+        };
+        sponge.absorb_bytes(&(sep.len() as u64).to_le_bytes());
+        sponge.absorb_bytes(sep.as_bytes());
+        sponge.pad_permute(DOMAIN_INIT);
+        sponge
+    }
+}
+
+impl<const RATE: usize> Hasher for KeccakSponge<RATE> {
+    fn write(&mut self, buf: &[u8]) {
+        if self.mode == Mode::Squeeze {
+            self.mode = Mode::Absorb;
+            self.permute();
+        }
+        self.absorb_bytes(buf)
+    }
+}
+
+impl<const RATE: usize> RngCore for KeccakSponge<RATE> {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if self.mode == Mode::Absorb {
+            self.mode = Mode::Squeeze;
+            self.pad_permute(DOMAIN_SQUEEZE);
+        }
+        for v in dest.iter_mut() {
+            if self.pos == RATE {
+                self.permute();
+            }
+            *v = self.get_byte(self.pos);
+            self.pos += 1;
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<const RATE: usize> CryptoRng for KeccakSponge<RATE> {}
+
+impl<const RATE: usize> Sampler for KeccakSponge<RATE> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector() {
+        let mut sponge = KeccakSponge::<136>::new("bevis");
+        sponge.write(b"abc");
+        let mut out = [0u8; 32];
+        sponge.fill_bytes(&mut out);
+        assert_eq!(
+            out,
+            [
+                0xc8, 0x4f, 0x78, 0xd3, 0x06, 0x1e, 0x66, 0xb0, 0x07, 0x2b, 0x3c, 0x91, 0x89, 0x66,
+                0xfc, 0xc1, 0x6a, 0xd2, 0x33, 0xad, 0x9e, 0xb8, 0xa1, 0xf1, 0x4d, 0x94, 0x56, 0xcc,
+                0x6b, 0xf4, 0x5e, 0x80
+            ]
+        );
+    }
+}
@@ -0,0 +1,32 @@
+// keccak-f[1600] duplex sponge
+#[cfg(feature = "keccak")]
+mod keccak;
+
+// cSHAKE128/cSHAKE256 sponges
+#[cfg(feature = "shake")]
+mod shake;
+
+// blake3 sponge
+#[cfg(feature = "blake3")]
+mod blake3;
+
+// poseidon sponge over a prime field
+#[cfg(feature = "poseidon")]
+mod poseidon;
+
+// tagged absorb mode
+mod tagged;
+
+pub use tagged::Tagged;
+
+#[cfg(feature = "keccak")]
+pub use keccak::KeccakSponge;
+
+#[cfg(feature = "shake")]
+pub use shake::{Shake128Sponge, Shake256Sponge};
+
+#[cfg(feature = "blake3")]
+pub use self::blake3::Blake3Sponge;
+
+#[cfg(feature = "poseidon")]
+pub use poseidon::{PoseidonParams, PoseidonSponge};
@@ -0,0 +1,713 @@
+use crate::absorb::repr_le;
+use crate::{Challenge, Ff, FieldHasher, FieldSampler, Hasher, Packed, Sampler, Sponge};
+
+use alloc::vec::Vec;
+
+use core::any::{Any, TypeId};
+
+use ff::PrimeField;
+
+use rand_core::{impls, CryptoRng, RngCore};
+
+// marker appended to every run of bytes before packing
+const BYTES_PAD: u8 = 0x01;
+
+// header element preceding every run of bytes,
+// runs of native elements are preceded by their length plus one
+const BYTES_RUN: u64 = 0;
+
+// element terminating the absorb phase before squeezing
+const END_ABSORB: u64 = 1;
+
+// maximum size of the canonical representation of field elements
+const MAX_REPR: usize = 64;
+
+// squeezed field elements are truncated to leave this many bits of slack
+const SECURITY: u32 = 128;
+
+// size of the grain LFSR state in bits
+const GRAIN_STATE: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Absorb,
+    Squeeze,
+}
+
+/// Parameters of the Poseidon permutation over `F` with width `T`.
+///
+/// Either generated like the reference implementation (`PoseidonParams::grain`)
+/// or given explicitly (`PoseidonParams::new`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoseidonParams<F: PrimeField, const T: usize> {
+    alpha: u64,
+    rf: usize,
+    rp: usize,
+    mds: [[F; T]; T],
+    constants: Vec<[F; T]>,
+}
+
+impl<F: PrimeField, const T: usize> PoseidonParams<F, T> {
+    /// Explicit parameters: the S-box exponent, the number of full rounds,
+    /// the MDS matrix and one row of round constants per round
+    /// (the number of partial rounds is `constants.len() - rf`).
+    pub fn new(alpha: u64, rf: usize, mds: [[F; T]; T], constants: Vec<[F; T]>) -> Self {
+        assert!(T >= 2, "poseidon width must be at least 2");
+        assert!(rf.is_multiple_of(2), "number of full rounds must be even");
+        assert!(constants.len() >= rf, "missing round constants");
+        Self {
+            alpha,
+            rf,
+            rp: constants.len() - rf,
+            mds,
+            constants,
+        }
+    }
+
+    /// Parameters generated by the Grain LFSR exactly as by the reference implementation
+    /// (`generate_parameters_grain.sage`) for an x^alpha S-box:
+    /// the round constants are sampled by rejection,
+    /// followed by a Cauchy MDS matrix `1 / (x_i + y_j)`.
+    ///
+    /// The reference implementation rejects MDS matrices which are not secure
+    /// (checked using algorithms 1-3 of "Proving Resistance Against Infinitely Long Subspace Trails"),
+    /// `select` is the number of matrices it rejects for the given parameters,
+    /// e.g. 0 for the pasta fields with `T = 3`, `RF = 8` and `RP = 56` (as in halo2).
+    pub fn grain(rf: usize, rp: usize, select: usize) -> Self {
+        let mut grain = Grain::new(F::NUM_BITS, T, rf, rp);
+        let constants = (0..rf + rp)
+            .map(|_| [(); T].map(|_| grain.field_element()))
+            .collect();
+
+        let mut select = select;
+        let mds = loop {
+            let vals: Vec<F> = (0..2 * T).map(|_| grain.field_element_reduced()).collect();
+            let distinct = vals
+                .iter()
+                .enumerate()
+                .all(|(i, x)| vals[..i].iter().all(|y| x != y));
+            if !distinct {
+                continue;
+            }
+            let (xs, ys) = vals.split_at(T);
+            let Some(mds) = cauchy::<F, T>(xs, ys) else {
+                continue;
+            };
+            if select == 0 {
+                break mds;
+            }
+            select -= 1;
+        };
+
+        Self::new(alpha::<F>(), rf, mds, constants)
+    }
+
+    /// Number of full rounds
+    pub fn full_rounds(&self) -> usize {
+        self.rf
+    }
+
+    /// Number of partial rounds
+    pub fn partial_rounds(&self) -> usize {
+        self.rp
+    }
+}
+
+// cauchy matrix M[i][j] = 1 / (x_i + y_j), if all the sums are non-zero
+fn cauchy<F: PrimeField, const T: usize>(xs: &[F], ys: &[F]) -> Option<[[F; T]; T]> {
+    let mut mds = [[F::ZERO; T]; T];
+    for (row, x) in mds.iter_mut().zip(xs) {
+        for (m, y) in row.iter_mut().zip(ys) {
+            *m = Option::from((*x + y).invert())?;
+        }
+    }
+    Some(mds)
+}
+
+/// Grain LFSR in self-shrinking mode,
+/// used by the reference implementation to generate the Poseidon parameters.
+struct Grain {
+    state: [bool; GRAIN_STATE],
+    head: usize, // position of the oldest bit
+}
+
+impl Grain {
+    fn new(n: u32, t: usize, rf: usize, rp: usize) -> Self {
+        // field type (prime field), S-box type (x^alpha), field size, width and rounds,
+        // each most significant bit first, followed by ones
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (n as usize, 12),
+            (t, 12),
+            (rf, 10),
+            (rp, 10),
+        ];
+        let mut state = [true; GRAIN_STATE];
+        let mut i = 0;
+        for (value, len) in fields {
+            assert!(value < 1 << len, "poseidon parameters too large");
+            for k in (0..len).rev() {
+                state[i] = (value >> k) & 1 == 1;
+                i += 1;
+            }
+        }
+
+        // discard the first 160 bits
+        let mut grain = Grain { state, head: 0 };
+        for _ in 0..2 * GRAIN_STATE {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = |k: usize| self.state[(self.head + k) % GRAIN_STATE];
+        let bit = s(62) ^ s(51) ^ s(38) ^ s(23) ^ s(13) ^ s(0);
+        self.state[self.head] = bit;
+        self.head = (self.head + 1) % GRAIN_STATE;
+        bit
+    }
+
+    // self-shrinking: output the second bit of the first pair starting with a one
+    fn next_bit(&mut self) -> bool {
+        while !self.step() {
+            self.step();
+        }
+        self.step()
+    }
+
+    // next NUM_BITS bits as an integer (most significant bit first), rejected if not below p
+    fn field_element<F: PrimeField>(&mut self) -> F {
+        loop {
+            let mut bytes = [0u8; MAX_REPR];
+            for i in (0..F::NUM_BITS as usize).rev() {
+                bytes[i / 8] |= (self.next_bit() as u8) << (i % 8);
+            }
+            let mut repr = F::Repr::default();
+            let dst = repr.as_mut();
+            let len = dst.len();
+            dst.copy_from_slice(&bytes[..len]);
+            if !repr_le::<F>() {
+                dst.reverse();
+            }
+            if let Some(elem) = F::from_repr_vartime(repr) {
+                return elem;
+            }
+        }
+    }
+
+    // next NUM_BITS bits as an integer (most significant bit first), reduced modulo p
+    fn field_element_reduced<F: PrimeField>(&mut self) -> F {
+        (0..F::NUM_BITS).fold(F::ZERO, |acc, _| {
+            let acc = acc.double();
+            if self.next_bit() {
+                acc + F::ONE
+            } else {
+                acc
+            }
+        })
+    }
+}
+
+/// Smallest prime alpha such that x -> x^alpha is a permutation,
+/// i.e. gcd(alpha, p - 1) = 1.
+fn alpha<F: PrimeField>() -> u64 {
+    let modulus = F::MODULUS.trim_start_matches("0x");
+    [3, 5, 7, 11, 13, 17, 19, 23]
+        .into_iter()
+        .find(|&a| {
+            let rem = modulus
+                .chars()
+                .map(|c| c.to_digit(16).expect("modulus must be hex") as u64)
+                .fold(0, |r, d| (r * 16 + d) % a);
+            (rem + a - 1) % a != 0
+        })
+        .expect("no small alpha for field")
+}
+
+/// Algebraic duplex sponge using the Poseidon permutation over a prime field.
+///
+/// The state consists of `T` field elements:
+/// a single capacity element and a rate of `T - 1` elements.
+/// The permutation has `RF` full and `RP` partial rounds.
+/// The defaults are the instance P128Pow5T3 of halo2 for the pasta fields
+/// (`T = 3`, `RF = 8`, `RP = 56` and alpha = 5),
+/// whose parameters `Sponge::new` generates using `PoseidonParams::grain(8, 56, 0)`.
+/// Other widths, rounds or fields require vetting the parameters
+/// (consult the Poseidon paper and the reference implementation)
+/// and creating the sponge using `with_params`.
+///
+/// - The S-box exponent alpha is the smallest prime coprime to p - 1.
+/// - The round constants and MDS matrix are generated like the reference implementation.
+///
+/// `Sponge::new` runs the Grain LFSR on every call:
+/// when creating many sponges, generate the parameters once
+/// and pass clones to `with_params` (or clone an initialized sponge).
+///
+/// Field elements are absorbed natively using `FieldHasher`
+/// and squeezed natively using `FieldSampler`
+/// (as by `Sponge::write_elems` / `Sponge::squeeze_elem` for elements of `F`).
+/// Squeezing bytes truncates every squeezed element to `(NUM_BITS - 128) / 8` bytes,
+/// hence byte squeezing requires fields of at least 136 bits.
+///
+/// Absorbed input is framed such that native elements and bytes never coincide:
+///
+/// - Every run of bytes written using `Hasher::write` is preceded by the element zero,
+///   padded with 0x01 and packed into elements of `(CAPACITY - 1) / 8` bytes,
+///   the last of which is marked by adding `2^(8 * ((CAPACITY - 1) / 8))`.
+/// - Every (non-empty) run of native elements is preceded by its length plus one.
+/// - Every absorb phase is terminated by absorbing one before squeezing.
+#[derive(Debug, Clone)]
+pub struct PoseidonSponge<
+    F: PrimeField,
+    const T: usize = 3,
+    const RF: usize = 8,
+    const RP: usize = 56,
+> {
+    params: PoseidonParams<F, T>,
+    state: [F; T],
+    mode: Mode,
+    pos: usize,
+
+    // pending bytes not yet packed into a field element
+    bytes: [u8; MAX_REPR],
+    bytes_len: usize,
+    bytes_run: bool,
+
+    // squeezed bytes not yet returned
+    out: [u8; MAX_REPR],
+    out_pos: usize,
+    out_len: usize,
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> PoseidonSponge<F, T, RF, RP> {
+    const RATE: usize = T - 1;
+
+    fn bytes_per_elem() -> usize {
+        let n = ((F::CAPACITY - 1) / 8) as usize;
+        assert!(n < MAX_REPR, "field too large");
+        n
+    }
+
+    /// Create a sponge using explicit parameters of the permutation
+    /// (which must have `RF` full and `RP` partial rounds).
+    pub fn with_params(sep: &str, params: PoseidonParams<F, T>) -> Self {
+        assert!(
+            params.rf == RF && params.rp == RP,
+            "poseidon parameters do not match the number of rounds"
+        );
+        let mut sponge = Self {
+            params,
+            state: [F::ZERO; T],
+            mode: Mode::Absorb,
+            pos: 0,
+            bytes: [0; MAX_REPR],
+            bytes_len: 0,
+            bytes_run: false,
+            out: [0; MAX_REPR],
+            out_pos: 0,
+            out_len: 0,
+        };
+        sponge.write(&(sep.len() as u64).to_le_bytes());
+        sponge.write(sep.as_bytes());
+        sponge.flush_bytes();
+        sponge
+    }
+
+    fn permute(&mut self) {
+        let half = RF / 2;
+        let alpha = [self.params.alpha];
+        for r in 0..RF + RP {
+            for (s, c) in self.state.iter_mut().zip(self.params.constants[r].iter()) {
+                *s += c;
+            }
+            if r < half || r >= half + RP {
+                for s in self.state.iter_mut() {
+                    *s = s.pow_vartime(alpha);
+                }
+            } else {
+                self.state[0] = self.state[0].pow_vartime(alpha);
+            }
+            self.state = self.params.mds.map(|row| {
+                row.iter()
+                    .zip(self.state.iter())
+                    .fold(F::ZERO, |acc, (m, s)| acc + *m * s)
+            });
+        }
+        self.pos = 0;
+    }
+
+    fn absorb_elem(&mut self, elem: F) {
+        if self.mode == Mode::Squeeze {
+            self.mode = Mode::Absorb;
+            self.discard_output();
+            self.permute();
+        }
+        if self.pos == Self::RATE {
+            self.permute();
+        }
+        self.state[1 + self.pos] += elem;
+        self.pos += 1;
+    }
+
+    /// Discard squeezed bytes not yet returned.
+    fn discard_output(&mut self) {
+        self.out_pos = 0;
+        self.out_len = 0;
+    }
+
+    /// Pack the pending bytes into a field element.
+    fn pack(&self) -> F {
+        let n = Self::bytes_per_elem();
+        let mut repr = F::Repr::default();
+        let dst = repr.as_mut();
+        if repr_le::<F>() {
+            dst[..n].copy_from_slice(&self.bytes[..n]);
+        } else {
+            let len = dst.len();
+            for (i, v) in self.bytes[..n].iter().enumerate() {
+                dst[len - 1 - i] = *v;
+            }
+        }
+        F::from_repr_vartime(repr).unwrap()
+    }
+
+    /// Absorb a run of native elements.
+    fn absorb_run<I: ExactSizeIterator<Item = F>>(&mut self, elems: I) {
+        self.flush_bytes();
+        if elems.len() > 0 {
+            self.absorb_elem(F::from(elems.len() as u64 + 1));
+            for elem in elems {
+                self.absorb_elem(elem);
+            }
+        }
+    }
+
+    fn flush_bytes(&mut self) {
+        if self.bytes_run {
+            // mark the last element of the run
+            let last = F::from(2).pow_vartime([8 * Self::bytes_per_elem() as u64]);
+            self.bytes[self.bytes_len] = BYTES_PAD;
+            let elem = self.pack() + last;
+            self.absorb_elem(elem);
+            self.bytes = [0; MAX_REPR];
+            self.bytes_len = 0;
+            self.bytes_run = false;
+        }
+    }
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> Sponge
+    for PoseidonSponge<F, T, RF, RP>
+{
+    fn new(sep: &str) -> Self {
+        assert!(
+            (T, RF, RP) == (3, 8, 56),
+            "poseidon parameters are only vetted for T = 3, RF = 8 and RP = 56, use with_params"
+        );
+        Self::with_params(sep, PoseidonParams::grain(RF, RP, 0))
+    }
+
+    fn write_elems<G: PrimeField>(&mut self, elems: &[G]) {
+        if TypeId::of::<G>() == TypeId::of::<F>() {
+            self.absorb_run(
+                elems
+                    .iter()
+                    .map(|elem| *(elem as &dyn Any).downcast_ref::<F>().unwrap()),
+            )
+        } else {
+            Packed(self).write_field(elems)
+        }
+    }
+
+    fn squeeze_elem<G: PrimeField>(&mut self) -> G {
+        if TypeId::of::<G>() == TypeId::of::<F>() {
+            let elem = self.squeeze_field();
+            *(&elem as &dyn Any).downcast_ref::<G>().unwrap()
+        } else {
+            Ff::<G>::sample(self).into_inner()
+        }
+    }
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> Hasher
+    for PoseidonSponge<F, T, RF, RP>
+{
+    fn write(&mut self, buf: &[u8]) {
+        let n = Self::bytes_per_elem();
+        self.discard_output();
+        if !self.bytes_run {
+            self.absorb_elem(F::from(BYTES_RUN));
+            self.bytes_run = true;
+        }
+        for v in buf {
+            self.bytes[self.bytes_len] = *v;
+            self.bytes_len += 1;
+            if self.bytes_len == n {
+                let elem = self.pack();
+                self.absorb_elem(elem);
+                self.bytes = [0; MAX_REPR];
+                self.bytes_len = 0;
+            }
+        }
+    }
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> RngCore
+    for PoseidonSponge<F, T, RF, RP>
+{
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(
+            F::NUM_BITS >= SECURITY + 8,
+            "field too small for squeezing bytes"
+        );
+        let n = ((F::NUM_BITS - SECURITY) / 8) as usize;
+        for v in dest.iter_mut() {
+            if self.out_pos == self.out_len {
+                // take the least significant bytes of the element
+                let elem = self.squeeze_field().to_repr();
+                let repr = elem.as_ref();
+                for i in 0..n {
+                    self.out[i] = if repr_le::<F>() {
+                        repr[i]
+                    } else {
+                        repr[repr.len() - 1 - i]
+                    };
+                }
+                self.out_pos = 0;
+                self.out_len = n;
+            }
+            *v = self.out[self.out_pos];
+            self.out_pos += 1;
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> CryptoRng
+    for PoseidonSponge<F, T, RF, RP>
+{
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> Sampler
+    for PoseidonSponge<F, T, RF, RP>
+{
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> FieldHasher<F>
+    for PoseidonSponge<F, T, RF, RP>
+{
+    fn write_field(&mut self, elems: &[F]) {
+        self.absorb_run(elems.iter().copied())
+    }
+}
+
+impl<F: PrimeField, const T: usize, const RF: usize, const RP: usize> FieldSampler<F>
+    for PoseidonSponge<F, T, RF, RP>
+{
+    fn squeeze_field(&mut self) -> F {
+        self.flush_bytes();
+        self.discard_output();
+        if self.mode == Mode::Absorb {
+            self.absorb_elem(F::from(END_ABSORB));
+            self.mode = Mode::Squeeze;
+            self.permute();
+        }
+        if self.pos == Self::RATE {
+            self.permute();
+        }
+        let elem = self.state[1 + self.pos];
+        self.pos += 1;
+        elem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pasta_curves::{Fp, Fq};
+
+    // poseidon over the pallas base field as in halo2 (P128Pow5T3)
+    type Pasta = PoseidonSponge<Fp>;
+
+    #[test]
+    fn grain_parameters() {
+        // known-answer values of the reference implementation
+        let params = PoseidonParams::<Fp, 3>::grain(8, 56, 0);
+        assert_eq!(params.alpha, 5);
+        assert_eq!(params.constants.len(), 64);
+        assert_eq!(
+            params.constants[0][0],
+            Fp::from_raw([
+                0x5753_8c25_9642_6303,
+                0x4e71_162f_3100_3b70,
+                0x353f_628f_76d1_10f3,
+                0x360d_7470_611e_473d,
+            ])
+        );
+        assert_eq!(
+            params.constants[0][1],
+            Fp::from_raw([
+                0xbdb7_4213_bf63_188b,
+                0x4908_ac2f_12eb_e06f,
+                0x5dc3_c6c5_febf_aa31,
+                0x2bab_94d7_ae22_2d13,
+            ])
+        );
+        assert_eq!(
+            params.constants[63][2],
+            Fp::from_raw([
+                0x1211_b9e2_190d_6852,
+                0xa004_abe8_e015_28c4,
+                0x5c1e_3e9e_27a5_71c3,
+                0x3a8a_6282_9512_1d5c,
+            ])
+        );
+        assert_eq!(
+            params.mds[0][0],
+            Fp::from_raw([
+                0x323f_2486_d7e1_1b63,
+                0x97d7_a0ab_2385_0b56,
+                0xb3d5_9fbd_c8c9_ead4,
+                0x0ab5_e5b8_74a6_8de7,
+            ])
+        );
+        assert_eq!(
+            params.mds[1][2],
+            Fp::from_raw([
+                0xf84d_806f_685f_747a,
+                0x9aad_3d82_62ef_d83f,
+                0x7493_8717_989a_1957,
+                0x22f5_b5e1_e608_1c97,
+            ])
+        );
+    }
+
+    #[test]
+    fn permutation() {
+        let mut sponge = Pasta::new("bevis");
+        sponge.state = [Fp::from(0), Fp::from(1), Fp::from(2)];
+        sponge.permute();
+        assert_eq!(
+            sponge.state,
+            [
+                Fp::from_raw([
+                    0xaeb1_bc02_4aec_a456,
+                    0xf7e6_9a71_d0b6_42a0,
+                    0x94ef_b364_f966_240f,
+                    0x2a52_6acd_0b64_b453,
+                ]),
+                Fp::from_raw([
+                    0x012a_3e96_28e5_b82a,
+                    0xdcd4_2e7f_bed9_dafe,
+                    0x76ff_7dae_343d_5512,
+                    0x13c5_d156_8b4a_a430,
+                ]),
+                Fp::from_raw([
+                    0x3590_29a1_d34e_9ddd,
+                    0xf7cf_dfe1_bda4_2c7b,
+                    0x256f_cd59_7984_561a,
+                    0x0a49_c868_c697_6544,
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_parameters() {
+        let params = PoseidonParams::grain(8, 56, 0);
+        let mut a = Pasta::new("bevis");
+        let mut b = Pasta::with_params("bevis", params);
+        a.write(b"abc");
+        b.write(b"abc");
+        assert_eq!(a.squeeze_field(), b.squeeze_field());
+    }
+
+    #[test]
+    #[should_panic(expected = "only vetted")]
+    fn unvetted_parameters() {
+        PoseidonSponge::<Fp, 3, 8, 60>::new("bevis");
+    }
+
+    #[test]
+    fn other_width() {
+        // other instances are created from explicit parameters
+        let params = PoseidonParams::<Fp, 5>::grain(8, 60, 0);
+        let mut a = PoseidonSponge::<Fp, 5, 8, 60>::with_params("bevis", params);
+        let mut b = Pasta::new("bevis");
+        a.write(b"abc");
+        b.write(b"abc");
+        a.fill_bytes(&mut [0u8; 100]);
+        assert_ne!(a.squeeze_field(), b.squeeze_field());
+    }
+
+    #[test]
+    fn absorb_after_squeeze() {
+        let mut a = Pasta::new("bevis");
+        let mut b = Pasta::new("bevis");
+        let mut buf = [0u8; 5];
+        a.fill_bytes(&mut buf);
+        b.fill_bytes(&mut buf);
+
+        // the challenge after absorbing must depend on the message
+        a.write(b"x");
+        b.write(b"y");
+        assert_ne!(a.next_u64(), b.next_u64());
+
+        // squeeze more than a single element after absorbing
+        a.write(b"z");
+        a.fill_bytes(&mut [0u8; 80]);
+    }
+
+    #[test]
+    fn native_separated_from_bytes() {
+        // the packing of b"ab" without framing
+        let mut a = Pasta::new("bevis");
+        let mut b = Pasta::new("bevis");
+        a.write_field(&[Fp::from(0x01_6261)]);
+        b.write(b"ab");
+        assert_ne!(a.squeeze_field(), b.squeeze_field());
+
+        // runs of native elements are length-prefixed
+        let mut a = Pasta::new("bevis");
+        let mut b = Pasta::new("bevis");
+        a.write_field(&[Fp::from(1), Fp::from(2)]);
+        b.write_field(&[Fp::from(1)]);
+        b.write_field(&[Fp::from(2)]);
+        assert_ne!(a.squeeze_field(), b.squeeze_field());
+    }
+
+    #[test]
+    fn write_elems() {
+        // native for elements of the field of the sponge
+        let mut a = Pasta::new("bevis");
+        let mut b = Pasta::new("bevis");
+        a.write_elems(&[Fp::from(3)]);
+        b.write_field(&[Fp::from(3)]);
+        assert_eq!(a.squeeze_elem::<Fp>(), b.squeeze_field());
+
+        // packed for elements of other fields
+        let mut a = Pasta::new("bevis");
+        let mut b = Pasta::new("bevis");
+        a.write_elems(&[Fq::from(3)]);
+        Packed(&mut b).write_field(&[Fq::from(3)]);
+        assert_eq!(
+            a.squeeze_elem::<Fq>(),
+            Ff::<Fq>::sample(&mut b).into_inner()
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{Hasher, Sampler, Sponge};
+
+use rand_core::{impls, CryptoRng, RngCore};
+
+// cSHAKE function name of the instances absorbing a chaining value
+const REKEY: &[u8] = b"bevis-rekey";
+
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{CShake128, CShake128Core, CShake128Reader, CShake256, CShake256Core, CShake256Reader};
+
+#[derive(Clone)]
+enum State<H, R> {
+    Absorb(H),
+    Squeeze(R),
+    Empty,
+}
+
+macro_rules! shake_sponge_impl {
+    ( $(#[$doc:meta])* $name:ident, $hasher:ty, $core:ty, $reader:ty, $chain:expr ) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            rekey: $hasher,
+            state: State<$hasher, $reader>,
+        }
+
+        impl $name {
+            /// Length of the chaining value (in bytes)
+            /// squeezed when re-keying after a squeeze.
+            pub const CHAIN: usize = $chain;
+
+            fn hasher(&mut self) -> &mut $hasher {
+                if let State::Squeeze(reader) = &mut self.state {
+                    // re-key: absorb a chaining value into a fresh cSHAKE instance,
+                    // with a distinct function name such that it never coincides with `new`
+                    let mut chain = [0u8; $chain];
+                    reader.read(&mut chain);
+                    let mut hasher = self.rekey.clone();
+                    hasher.update(&chain);
+                    self.state = State::Absorb(hasher);
+                }
+                match &mut self.state {
+                    State::Absorb(hasher) => hasher,
+                    _ => unreachable!(),
+                }
+            }
+
+            fn reader(&mut self) -> &mut $reader {
+                if let State::Absorb(_) = self.state {
+                    match core::mem::replace(&mut self.state, State::Empty) {
+                        State::Absorb(hasher) => {
+                            self.state = State::Squeeze(hasher.finalize_xof());
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                match &mut self.state {
+                    State::Squeeze(reader) => reader,
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}", stringify!($name))
+            }
+        }
+
+        impl Sponge for $name {
+            fn new(sep: &str) -> Self {
+                Self {
+                    state: State::Absorb(<$hasher>::from_core(<$core>::new(sep.as_bytes()))),
+                    rekey: <$hasher>::from_core(<$core>::new_with_function_name(
+                        REKEY,
+                        sep.as_bytes(),
+                    )),
+                }
+            }
+        }
+
+        impl Hasher for $name {
+            fn write(&mut self, buf: &[u8]) {
+                self.hasher().update(buf)
+            }
+        }
+
+        impl RngCore for $name {
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                self.reader().read(dest)
+            }
+
+            fn next_u32(&mut self) -> u32 {
+                impls::next_u32_via_fill(self)
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                impls::next_u64_via_fill(self)
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        impl CryptoRng for $name {}
+
+        impl Sampler for $name {}
+    };
+}
+
+shake_sponge_impl!(
+    /// Sponge based on cSHAKE128 (NIST SP 800-185).
+    ///
+    /// The separator is used as the cSHAKE customization string
+    /// (with an empty function name), hence `new("")` is plain SHAKE128.
+    ///
+    /// Squeezing finalizes the XOF. Writing after squeezing re-keys the sponge:
+    /// a 32 byte chaining value is squeezed and absorbed into a fresh
+    /// cSHAKE128 instance with the same customization and function name `"bevis-rekey"`.
+    Shake128Sponge,
+    CShake128,
+    CShake128Core,
+    CShake128Reader,
+    32
+);
+
+shake_sponge_impl!(
+    /// Sponge based on cSHAKE256 (NIST SP 800-185).
+    ///
+    /// The separator is used as the cSHAKE customization string
+    /// (with an empty function name), hence `new("")` is plain SHAKE256.
+    ///
+    /// Squeezing finalizes the XOF. Writing after squeezing re-keys the sponge:
+    /// a 64 byte chaining value is squeezed and absorbed into a fresh
+    /// cSHAKE256 instance with the same customization and function name `"bevis-rekey"`.
+    Shake256Sponge,
+    CShake256,
+    CShake256Core,
+    CShake256Reader,
+    64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rekeyed<S: Sponge + Clone>(chain: usize) -> ([u8; 32], [u8; 32]) {
+        // continue a transcript after squeezing
+        let mut sponge = S::new("bevis");
+        sponge.write(b"x");
+        let mut challenge = [0u8; 16];
+        sponge.fill_bytes(&mut challenge);
+
+        // the chaining value absorbed when re-keying
+        let mut chain_value = [0u8; 64];
+        sponge.clone().fill_bytes(&mut chain_value[..chain]);
+
+        sponge.write(b"y");
+        let mut continued = [0u8; 32];
+        sponge.fill_bytes(&mut continued);
+
+        // a fresh transcript absorbing the same bytes as the re-keyed instance
+        let mut fresh = S::new("bevis");
+        fresh.write(&chain_value[..chain]);
+        fresh.write(b"y");
+        let mut out = [0u8; 32];
+        fresh.fill_bytes(&mut out);
+        (continued, out)
+    }
+
+    #[test]
+    fn rekey_separated_from_new() {
+        let (continued, fresh) = rekeyed::<Shake128Sponge>(Shake128Sponge::CHAIN);
+        assert_ne!(continued, fresh);
+        let (continued, fresh) = rekeyed::<Shake256Sponge>(Shake256Sponge::CHAIN);
+        assert_ne!(continued, fresh);
+    }
+}
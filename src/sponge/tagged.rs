@@ -0,0 +1,130 @@
+use crate::{FieldHasher, FieldSampler, Hasher, Sampler, Sponge};
+
+use rand_core::{CryptoRng, RngCore};
+
+/// Enables the tagged absorb mode for a sponge:
+/// in addition to the default encoding, the identifiers of
+/// structs, fields, enums and variants are absorbed (as length-prefixed strings).
+///
+/// Hence `struct A { x: u64 }` and `struct B { y: u64 }` absorb differently,
+/// at the cost of a larger encoding.
+/// Use e.g. `SpongeTranscript<Tagged<KeccakSponge>>` to enable it for a transcript.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct Tagged<S: Sponge>(S);
+
+impl<S: Sponge> Sponge for Tagged<S> {
+    fn new(sep: &str) -> Self {
+        Self(S::new(sep))
+    }
+
+    #[cfg(feature = "ff")]
+    #[inline(always)]
+    fn write_elems<F: ff::PrimeField>(&mut self, elems: &[F]) {
+        self.0.write_elems(elems)
+    }
+
+    #[cfg(feature = "ff")]
+    #[inline(always)]
+    fn squeeze_elem<F: ff::PrimeField>(&mut self) -> F {
+        self.0.squeeze_elem()
+    }
+}
+
+impl<S: Sponge> Hasher for Tagged<S> {
+    const TAGGED: bool = true;
+
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) {
+        self.0.write(buf)
+    }
+}
+
+impl<S: Sponge> RngCore for Tagged<S> {
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl<S: Sponge> CryptoRng for Tagged<S> {}
+
+impl<S: Sponge> Sampler for Tagged<S> {}
+
+impl<F, S: Sponge + FieldHasher<F>> FieldHasher<F> for Tagged<S> {
+    #[inline(always)]
+    fn write_field(&mut self, elems: &[F]) {
+        self.0.write_field(elems)
+    }
+}
+
+impl<F, S: Sponge + FieldSampler<F>> FieldSampler<F> for Tagged<S> {
+    #[inline(always)]
+    fn squeeze_field(&mut self) -> F {
+        self.0.squeeze_field()
+    }
+}
+
+#[cfg(all(test, feature = "keccak"))]
+mod tests {
+    use super::*;
+    use crate::{KeccakSponge, SpongeTranscript, Transcript};
+
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct A {
+        x: u64,
+    }
+
+    #[derive(Serialize)]
+    struct B {
+        y: u64,
+    }
+
+    fn challenge<S: Sponge, T: Serialize>(msg: &T) -> u64 {
+        let mut ts = SpongeTranscript::<S>::new("bevis");
+        ts.append(msg);
+        ts.challenge()
+    }
+
+    #[test]
+    fn identifiers() {
+        // the names of the structs and fields are absorbed
+        assert_ne!(
+            challenge::<Tagged<KeccakSponge>, _>(&A { x: 1 }),
+            challenge::<Tagged<KeccakSponge>, _>(&B { y: 1 })
+        );
+
+        // but not by default
+        assert_eq!(
+            challenge::<KeccakSponge, _>(&A { x: 1 }),
+            challenge::<KeccakSponge, _>(&B { y: 1 })
+        );
+
+        // values without identifiers absorb identically in both modes
+        assert_ne!(
+            challenge::<Tagged<KeccakSponge>, _>(&A { x: 1 }),
+            challenge::<KeccakSponge, _>(&A { x: 1 })
+        );
+        assert_eq!(
+            challenge::<Tagged<KeccakSponge>, _>(&1u64),
+            challenge::<KeccakSponge, _>(&1u64)
+        );
+    }
+}
@@ -0,0 +1,528 @@
+use crate::absorb::AbsorbDeserializer;
+use crate::transcript::{pow, GRIND};
+use crate::{
+    Absorb, AbsorbError, Challenge, DecodeError, IndexSampling, Msg, Sampler, Separator, Transcript,
+};
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use core::cell::{Ref, RefCell};
+
+use rand_core::{CryptoRng, RngCore};
+
+use serde::de::DeserializeOwned;
+
+/// Prover transcript which records every message sent
+/// (`send` / `send_labeled`) into a proof stream.
+///
+/// Messages are written using the (untagged) absorb encoding,
+/// in the order they are sent: the resulting bytes are the proof,
+/// to be read back by a `ReplayTranscript` performing the same interaction.
+/// Hence simple protocols do not need a hand-written proof struct.
+///
+/// Clones (in particular the children of `fork`) record into the same proof stream,
+/// hence the messages sent by a child are part of the proof (in the order they are sent).
+#[derive(Debug, Clone)]
+pub struct RecordingTranscript<T: Transcript> {
+    ts: T,
+    proof: Rc<RefCell<Vec<u8>>>,
+}
+
+/// Verifier transcript which reads the messages of a recorded proof stream.
+///
+/// Every `read` decodes the next message from the stream and absorbs it (as by `recv`),
+/// use `finish` to check that the stream has been consumed entirely.
+///
+/// Clones (in particular the children of `fork`) read from the same proof stream,
+/// matching `RecordingTranscript`.
+#[derive(Clone)]
+pub struct ReplayTranscript<'a, T: Transcript> {
+    ts: T,
+    de: Rc<RefCell<AbsorbDeserializer<'a>>>,
+}
+
+/// Transcripts reading the messages of the prover from a proof stream
+/// (see `ReplayTranscript`).
+///
+/// Reading is a separate trait such that protocols reading a proof stream
+/// only accept transcripts which have one (e.g. `Arthur<ReplayTranscript<T>>`).
+pub trait ProofStream: Transcript {
+    /// Read the next message from the proof stream and absorb it (as by `recv`)
+    fn read<A: Absorb + DeserializeOwned>(&mut self) -> Result<A, DecodeError>;
+
+    /// Read the next message from the proof stream and absorb it with a label
+    /// (matching `send_labeled`)
+    fn read_labeled<A: Absorb + DeserializeOwned>(
+        &mut self,
+        label: &'static str,
+    ) -> Result<A, DecodeError>;
+
+    /// Read a proof-of-work nonce from the proof stream and check it
+    /// (matching `grind`, see `verify_grind`)
+    fn read_grind(&mut self, bits: u32) -> Result<bool, DecodeError>
+    where
+        Self: Clone,
+    {
+        assert!(bits <= 64, "at most 64 bits of grinding");
+        let child = self.fork(GRIND);
+        let nonce = self.read()?;
+        Ok(pow(&child, bits, nonce))
+    }
+}
+
+impl<T: Transcript> RecordingTranscript<T> {
+    pub fn new(ts: T) -> Self {
+        Self {
+            ts,
+            proof: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// The messages recorded so far
+    pub fn proof(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.proof.borrow(), Vec::as_slice)
+    }
+
+    /// Returns the proof stream
+    /// (taking the messages recorded by the clones of the transcript)
+    pub fn finish(self) -> Vec<u8> {
+        self.proof.take()
+    }
+}
+
+impl<'a, T: Transcript> ReplayTranscript<'a, T> {
+    pub fn new(ts: T, proof: &'a [u8]) -> Self {
+        Self {
+            ts,
+            de: Rc::new(RefCell::new(AbsorbDeserializer::new(proof))),
+        }
+    }
+
+    /// Checks that the proof stream has been consumed and returns the inner transcript
+    pub fn finish(self) -> Result<T, DecodeError> {
+        match self.de.borrow().remaining() {
+            0 => Ok(self.ts),
+            n => Err(DecodeError::TrailingBytes(n)),
+        }
+    }
+}
+
+#[cfg(feature = "safe")]
+impl<T: Transcript + Clone> RecordingTranscript<T> {
+    /// Run the prover of a stream proof, recording its messages
+    /// (the statement is bound as by `Bevis::prove`).
+    pub fn prove_stream<R: RngCore + CryptoRng, P: crate::StreamProof>(
+        &mut self,
+        crs: &P::CRS,      // common reference string (constant)
+        st: &P::Statement, // statement
+        wit: &P::Witness,  // witness
+        rng: &mut R,       // sampling of randomness
+    ) -> Result<(), P::Error>
+    where
+        P::Statement: Absorb,
+    {
+        self.append(&P::NAME);
+        self.append(st);
+        P::create(crs, st, wit, rng, &mut crate::Merlin::new(self))
+    }
+}
+
+#[cfg(feature = "safe")]
+impl<'a, T: Transcript + Clone> ReplayTranscript<'a, T> {
+    /// Run the verifier of a stream proof, reading the messages from the proof stream
+    /// (the statement is bound as by `Bevis::verify`).
+    ///
+    /// Does not check that the stream has been consumed entirely (see `finish`).
+    pub fn verify_stream<P: crate::StreamProof>(
+        &mut self,
+        crs: &P::CRS, // this must be a fixed value.
+        st: &P::Statement,
+    ) -> Result<P::Result, P::Error>
+    where
+        P::Statement: Absorb,
+    {
+        self.append(&P::NAME);
+        self.append(st);
+        P::consume(crs, st, &mut crate::Arthur::new(self))
+    }
+}
+
+impl<T: Transcript> RngCore for RecordingTranscript<T> {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.ts.fill_bytes(dest)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.ts.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.ts.next_u64()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.ts.try_fill_bytes(dest)
+    }
+}
+
+impl<T: Transcript> CryptoRng for RecordingTranscript<T> {}
+
+impl<T: Transcript> Sampler for RecordingTranscript<T> {}
+
+impl<T: Transcript> Transcript for RecordingTranscript<T> {
+    fn append<A: Absorb>(&mut self, elem: &A) {
+        self.ts.append(elem)
+    }
+
+    fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+        self.ts.try_append(elem)
+    }
+
+    fn challenge<C: Challenge>(&mut self) -> C {
+        self.ts.challenge()
+    }
+
+    fn separate(&mut self, sep: Separator, label: &str) {
+        self.ts.separate(sep, label)
+    }
+
+    fn recv<A: Absorb>(&mut self, msg: Msg<A>) -> A {
+        self.ts.recv(msg)
+    }
+
+    fn send<A: Absorb>(&mut self, elem: A) -> Msg<A> {
+        let msg = self.ts.send(elem);
+        msg.0.absorb(&mut *self.proof.borrow_mut());
+        msg
+    }
+
+    fn try_recv<A: Absorb>(&mut self, msg: Msg<A>) -> Result<A, AbsorbError> {
+        self.ts.try_recv(msg)
+    }
+
+    fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+        self.ts.append_labeled(label, elem)
+    }
+
+    fn recv_labeled<A: Absorb>(&mut self, label: &'static str, msg: Msg<A>) -> A {
+        self.ts.recv_labeled(label, msg)
+    }
+
+    fn send_labeled<A: Absorb>(&mut self, label: &'static str, elem: A) -> Msg<A> {
+        let msg = self.ts.send_labeled(label, elem);
+        msg.0.absorb(&mut *self.proof.borrow_mut());
+        msg
+    }
+
+    fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+        self.ts.challenge_labeled(label)
+    }
+
+    fn challenge_bytes(&mut self, dest: &mut [u8]) {
+        self.ts.challenge_bytes(dest)
+    }
+
+    fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+        self.ts.challenge_bytes_labeled(label, dest)
+    }
+
+    fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+        self.ts.append_bytes_labeled(label, bytes)
+    }
+
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        self.ts.append_field(elem)
+    }
+
+    #[cfg(feature = "ff")]
+    fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+        self.ts.challenge_field()
+    }
+
+    fn challenge_fill<C: Challenge>(&mut self, dest: &mut [C]) {
+        self.ts.challenge_fill(dest)
+    }
+
+    fn challenge_vec<C: Challenge>(&mut self, n: usize) -> Vec<C> {
+        self.ts.challenge_vec(n)
+    }
+
+    fn challenge_range(&mut self, n: u64) -> u64 {
+        self.ts.challenge_range(n)
+    }
+
+    fn challenge_indices(&mut self, n: u64, k: usize, mode: IndexSampling) -> Vec<u64> {
+        self.ts.challenge_indices(n, k, mode)
+    }
+}
+
+impl<'a, T: Transcript> RngCore for ReplayTranscript<'a, T> {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.ts.fill_bytes(dest)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.ts.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.ts.next_u64()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.ts.try_fill_bytes(dest)
+    }
+}
+
+impl<'a, T: Transcript> CryptoRng for ReplayTranscript<'a, T> {}
+
+impl<'a, T: Transcript> Sampler for ReplayTranscript<'a, T> {}
+
+impl<'a, T: Transcript> Transcript for ReplayTranscript<'a, T> {
+    fn append<A: Absorb>(&mut self, elem: &A) {
+        self.ts.append(elem)
+    }
+
+    fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+        self.ts.try_append(elem)
+    }
+
+    fn challenge<C: Challenge>(&mut self) -> C {
+        self.ts.challenge()
+    }
+
+    fn separate(&mut self, sep: Separator, label: &str) {
+        self.ts.separate(sep, label)
+    }
+
+    fn recv<A: Absorb>(&mut self, msg: Msg<A>) -> A {
+        self.ts.recv(msg)
+    }
+
+    fn send<A: Absorb>(&mut self, elem: A) -> Msg<A> {
+        self.ts.send(elem)
+    }
+
+    fn try_recv<A: Absorb>(&mut self, msg: Msg<A>) -> Result<A, AbsorbError> {
+        self.ts.try_recv(msg)
+    }
+
+    fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+        self.ts.append_labeled(label, elem)
+    }
+
+    fn recv_labeled<A: Absorb>(&mut self, label: &'static str, msg: Msg<A>) -> A {
+        self.ts.recv_labeled(label, msg)
+    }
+
+    fn send_labeled<A: Absorb>(&mut self, label: &'static str, elem: A) -> Msg<A> {
+        self.ts.send_labeled(label, elem)
+    }
+
+    fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+        self.ts.challenge_labeled(label)
+    }
+
+    fn challenge_bytes(&mut self, dest: &mut [u8]) {
+        self.ts.challenge_bytes(dest)
+    }
+
+    fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+        self.ts.challenge_bytes_labeled(label, dest)
+    }
+
+    fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+        self.ts.append_bytes_labeled(label, bytes)
+    }
+
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        self.ts.append_field(elem)
+    }
+
+    #[cfg(feature = "ff")]
+    fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+        self.ts.challenge_field()
+    }
+
+    fn challenge_fill<C: Challenge>(&mut self, dest: &mut [C]) {
+        self.ts.challenge_fill(dest)
+    }
+
+    fn challenge_vec<C: Challenge>(&mut self, n: usize) -> Vec<C> {
+        self.ts.challenge_vec(n)
+    }
+
+    fn challenge_range(&mut self, n: u64) -> u64 {
+        self.ts.challenge_range(n)
+    }
+
+    fn challenge_indices(&mut self, n: u64, k: usize, mode: IndexSampling) -> Vec<u64> {
+        self.ts.challenge_indices(n, k, mode)
+    }
+}
+
+impl<'a, T: Transcript> ProofStream for ReplayTranscript<'a, T> {
+    fn read<A: Absorb + DeserializeOwned>(&mut self) -> Result<A, DecodeError> {
+        let elem = A::deserialize(&mut *self.de.borrow_mut())?;
+        Ok(self.ts.recv(Msg(elem)))
+    }
+
+    fn read_labeled<A: Absorb + DeserializeOwned>(
+        &mut self,
+        label: &'static str,
+    ) -> Result<A, DecodeError> {
+        let elem = A::deserialize(&mut *self.de.borrow_mut())?;
+        Ok(self.ts.recv_labeled(label, Msg(elem)))
+    }
+}
+
+#[cfg(all(test, feature = "keccak"))]
+mod tests {
+    use super::*;
+    use crate::{KeccakSponge, SpongeTranscript};
+
+    use alloc::string::String;
+
+    type Ts = SpongeTranscript<KeccakSponge>;
+
+    #[test]
+    fn replay() {
+        let mut prover = RecordingTranscript::new(Ts::new("bevis"));
+        prover.send(1u64);
+        let c: u32 = prover.challenge();
+        prover.send_labeled("name", String::from("abc"));
+        prover.send((Some(c), [7u8; 3]));
+        let nonce = prover.grind(4);
+        let expected: u64 = prover.challenge();
+        let proof = prover.finish();
+
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &proof);
+        assert_eq!(verifier.read::<u64>(), Ok(1));
+        assert_eq!(verifier.challenge::<u32>(), c);
+        assert_eq!(verifier.read_labeled::<String>("name").unwrap(), "abc");
+        assert_eq!(verifier.read(), Ok((Some(c), [7u8; 3])));
+        assert_eq!(verifier.read_grind(4), Ok(true));
+        assert_eq!(verifier.challenge::<u64>(), expected);
+        verifier.finish().unwrap();
+
+        // the nonce is the last message
+        assert_eq!(proof[proof.len() - 8..], nonce.0.to_le_bytes());
+
+        // and found as by the recorded transcript
+        let mut direct = Ts::new("bevis");
+        direct.append(&1u64);
+        let _: u32 = direct.challenge();
+        direct.append_labeled("name", &"abc");
+        direct.append(&(Some(c), [7u8; 3]));
+        assert_eq!(direct.grind(4).0, nonce.0);
+    }
+
+    #[test]
+    fn errors() {
+        let mut prover = RecordingTranscript::new(Ts::new("bevis"));
+        prover.send(1u64);
+        let proof = prover.finish();
+
+        // reading past the end
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &proof);
+        assert_eq!(verifier.read::<u64>(), Ok(1));
+        assert_eq!(verifier.read::<u8>(), Err(DecodeError::UnexpectedEnd));
+
+        // stream not consumed
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &proof);
+        assert_eq!(verifier.read::<u32>(), Ok(1));
+        assert_eq!(verifier.finish().err(), Some(DecodeError::TrailingBytes(4)));
+    }
+
+    #[test]
+    fn fork() {
+        let mut prover = RecordingTranscript::new(Ts::new("bevis"));
+        prover.send(1u8);
+        let mut child = prover.fork("sub");
+        child.send(2u8);
+        let c: u64 = child.challenge();
+        prover.send(3u8);
+        let proof = prover.finish();
+
+        // the messages of the child are part of the proof
+        assert_eq!(proof, [1, 2, 3]);
+
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &proof);
+        assert_eq!(verifier.read(), Ok(1u8));
+        let mut child = verifier.fork("sub");
+        assert_eq!(child.read(), Ok(2u8));
+        assert_eq!(child.challenge::<u64>(), c);
+        assert_eq!(verifier.read(), Ok(3u8));
+        verifier.finish().unwrap();
+    }
+
+    #[cfg(feature = "safe")]
+    #[test]
+    fn bevis() {
+        use crate::{CryptoRng, Safe, Sponge, StreamProof};
+
+        // proof of knowledge of `x` with `x * x = st`, carried by the proof stream
+        struct Root;
+
+        impl StreamProof for Root {
+            type CRS = ();
+            type Error = DecodeError;
+            type Result = ();
+            type Statement = u64;
+            type Witness = u64;
+
+            const NAME: &'static str = "root";
+
+            fn consume<T: ProofStream + Safe>(
+                _crs: &(),
+                st: &u64,
+                ts: &mut T,
+            ) -> Result<(), DecodeError> {
+                let x: u64 = ts.read_labeled("x")?;
+                match x.checked_mul(x) == Some(*st) {
+                    true => Ok(()),
+                    false => Err(DecodeError::Invalid),
+                }
+            }
+
+            fn create<T: Transcript + Safe, R: RngCore + CryptoRng>(
+                _crs: &(),
+                _st: &u64,
+                wit: &u64,
+                _rng: &mut R,
+                ts: &mut T,
+            ) -> Result<(), DecodeError> {
+                ts.send_labeled("x", *wit);
+                Ok(())
+            }
+        }
+
+        let mut rng = KeccakSponge::<136>::new("rng");
+        let mut prover = RecordingTranscript::new(Ts::new("bevis"));
+        prover
+            .prove_stream::<_, Root>(&(), &9, &3, &mut rng)
+            .unwrap();
+        let expected: u64 = prover.challenge();
+        let proof = prover.finish();
+
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &proof);
+        verifier.verify_stream::<Root>(&(), &9).unwrap();
+        let mut verifier = verifier.finish().unwrap();
+        assert_eq!(verifier.challenge::<u64>(), expected);
+
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &proof);
+        assert_eq!(
+            verifier.verify_stream::<Root>(&(), &4),
+            Err(DecodeError::Invalid)
+        );
+
+        let mut verifier = ReplayTranscript::new(Ts::new("bevis"), &[]);
+        assert_eq!(
+            verifier.verify_stream::<Root>(&(), &9),
+            Err(DecodeError::UnexpectedEnd)
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{Absorb, AbsorbError, Challenge, Sampler, Separator, Transcript};
+
+use alloc::vec::Vec;
+
+use core::fmt;
+
+use rand_core::{impls, CryptoRng, RngCore};
+
+/// Transcript compatible with the Merlin transcript construction (STROBE-128).
+///
+/// Operations map onto labelled Merlin operations as follows:
+///
+/// - `append`, `recv` and `send` absorb the element into a buffer
+///   and call `append_message(LABEL_MESSAGE, buffer)`,
+///   `append_field` likewise absorbs the canonical packing of the elements.
+/// - `challenge_bytes` and every call to `fill_bytes`
+///   (hence every primitive read made by `Challenge::sample`)
+///   is one `challenge_bytes(LABEL_CHALLENGE, dest)`.
+///
+/// - The labelled variants (`append_labeled`, `challenge_labeled`, ...)
+///   use the given label as the Merlin label instead.
+/// - `separate(sep, label)` is `append_message(b"bevis-label", label)`
+///   (resp. `b"bevis-fork-child"`, `b"bevis-fork-parent"`)
+///   and `fork` clones the Merlin transcript.
+/// - `append_bytes_labeled(label, bytes)` is `append_message(label, bytes)`
+///   and `challenge_bytes_labeled(label, dest)` is `challenge_bytes(label, dest)`,
+///   without any additional encoding.
+///
+/// E.g. a `Msg<CompressedRistretto>` absorbs the 32 compressed bytes,
+/// and `Scalar::random(ts)` reads 64 challenge bytes in a single operation,
+/// matching the usual dalek-ecosystem pattern.
+/// Note that byte strings absorbed with `append_labeled` are length-prefixed
+/// and that `challenge::<[u8; N]>()` reads every byte separately:
+/// use `append_bytes_labeled` / `challenge_bytes_labeled` to match Merlin provers.
+/// Provers using non-UTF-8 labels can be reproduced using `append_message`.
+///
+/// Not to be confused with `bevis::Merlin`, the prover side of `Bevis::prove`
+/// (named after the prover of Arthur-Merlin protocols), which wraps any transcript,
+/// including this one.
+#[derive(Clone)]
+pub struct MerlinTranscript(merlin::Transcript);
+
+impl MerlinTranscript {
+    /// Label used for messages absorbed with `append`, `recv` and `send`.
+    pub const LABEL_MESSAGE: &'static [u8] = b"msg";
+
+    /// Label used for challenge bytes read by `challenge`, `challenge_bytes`
+    /// and the `RngCore` methods.
+    pub const LABEL_CHALLENGE: &'static [u8] = b"challenge";
+
+    pub fn new(label: &'static [u8]) -> Self {
+        Self(merlin::Transcript::new(label))
+    }
+
+    /// Append a message with an explicit Merlin label.
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.0.append_message(label, message)
+    }
+
+    /// Returns the underlying Merlin transcript.
+    pub fn into_inner(self) -> merlin::Transcript {
+        self.0
+    }
+}
+
+impl From<merlin::Transcript> for MerlinTranscript {
+    fn from(ts: merlin::Transcript) -> Self {
+        Self(ts)
+    }
+}
+
+impl fmt::Debug for MerlinTranscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MerlinTranscript")
+    }
+}
+
+impl RngCore for MerlinTranscript {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.challenge_bytes(Self::LABEL_CHALLENGE, dest)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for MerlinTranscript {}
+
+// reads challenge bytes under a caller-provided label
+struct LabeledChallenge<'a> {
+    ts: &'a mut merlin::Transcript,
+    label: &'static [u8],
+}
+
+impl RngCore for LabeledChallenge<'_> {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.ts.challenge_bytes(self.label, dest)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for LabeledChallenge<'_> {}
+
+impl Sampler for MerlinTranscript {}
+
+impl Transcript for MerlinTranscript {
+    fn append<A: Absorb>(&mut self, elem: &A) {
+        let mut msg = Vec::new();
+        elem.absorb(&mut msg);
+        self.0.append_message(Self::LABEL_MESSAGE, &msg)
+    }
+
+    fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+        let mut msg = Vec::new();
+        elem.try_absorb(&mut msg)?;
+        self.0.append_message(Self::LABEL_MESSAGE, &msg);
+        Ok(())
+    }
+
+    fn challenge<C: Challenge>(&mut self) -> C {
+        C::sample(self)
+    }
+
+    fn separate(&mut self, sep: Separator, label: &str) {
+        let sep: &'static [u8] = match sep {
+            Separator::Label => b"bevis-label",
+            Separator::ForkChild => b"bevis-fork-child",
+            Separator::ForkParent => b"bevis-fork-parent",
+        };
+        self.0.append_message(sep, label.as_bytes())
+    }
+
+    fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+        let mut msg = Vec::new();
+        elem.absorb(&mut msg);
+        self.0.append_message(label.as_bytes(), &msg)
+    }
+
+    fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+        C::sample(&mut LabeledChallenge {
+            ts: &mut self.0,
+            label: label.as_bytes(),
+        })
+    }
+
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        let mut msg = Vec::new();
+        elem.absorb_field(&mut crate::Packed(&mut msg));
+        self.0.append_message(Self::LABEL_MESSAGE, &msg)
+    }
+
+    fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+        self.0.challenge_bytes(label.as_bytes(), dest)
+    }
+
+    fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+        self.0.append_message(label.as_bytes(), bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Msg;
+
+    #[test]
+    fn matches_merlin() {
+        let mut ts = MerlinTranscript::new(b"bevis-test");
+        let mut mt = merlin::Transcript::new(b"bevis-test");
+
+        ts.append_bytes_labeled("point", &[7u8; 32]);
+        mt.append_message(b"point", &[7u8; 32]);
+
+        ts.append_labeled("n", &42u64);
+        mt.append_u64(b"n", 42);
+
+        ts.recv(Msg(0x0102u16));
+        mt.append_message(b"msg", &[0x02, 0x01]);
+
+        let mut c = [0u8; 64];
+        let mut d = [0u8; 64];
+        ts.challenge_bytes_labeled("c", &mut c);
+        mt.challenge_bytes(b"c", &mut d);
+        assert_eq!(c, d);
+
+        // unlabelled challenges: a single read per primitive / byte string
+        let mut c = [0u8; 4];
+        let mut d = [0u8; 4];
+        ts.challenge_bytes(&mut c);
+        mt.challenge_bytes(b"challenge", &mut d);
+        assert_eq!(c, d);
+
+        let mut d = [0u8; 8];
+        mt.challenge_bytes(b"challenge", &mut d);
+        assert_eq!(ts.challenge::<u64>(), u64::from_le_bytes(d));
+    }
+
+    #[test]
+    fn labeled_challenge() {
+        let mut ts = MerlinTranscript::new(b"bevis-test");
+        let mut mt = merlin::Transcript::new(b"bevis-test");
+
+        let mut d = [0u8; 4];
+        mt.challenge_bytes(b"x", &mut d);
+        assert_eq!(ts.challenge_labeled::<u32>("x"), u32::from_le_bytes(d));
+    }
+}
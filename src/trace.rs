@@ -1,4 +1,9 @@
-use crate::{Absorb, Challenge, Hasher, Msg, Sampler, Transcript};
+use crate::{
+    Absorb, AbsorbError, Challenge, DecodeError, IndexSampling, Msg, ProofStream, Sampler,
+    Separator, Transcript,
+};
+
+use crate::transcript::{try_write_append, write_append, write_separator};
 
 use alloc::format;
 use alloc::string::{String, ToString};
@@ -10,24 +15,37 @@ use core::fmt::Debug;
 
 use rand_core::{CryptoRng, RngCore};
 
-#[derive(Debug, PartialEq)]
+use serde::de::DeserializeOwned;
+
+// bytes absorbed by a `SpongeTranscript` for a message (preceded by its label)
+fn absorbed<A: Absorb>(label: Option<&str>, elem: &A) -> Vec<u8> {
+    let mut hsh = Vec::new();
+    if let Some(label) = label {
+        write_separator(&mut hsh, Separator::Label, label);
+    }
+    write_append(&mut hsh, elem);
+    hsh
+}
+
+fn try_absorbed<A: Absorb>(elem: &A) -> Result<Vec<u8>, AbsorbError> {
+    let mut hsh = Vec::new();
+    try_write_append(&mut hsh, elem)?;
+    Ok(hsh)
+}
+
+#[derive(Debug, Clone, PartialEq)]
 enum OpType {
     Challenge(&'static str),
+    Challenges(&'static str, usize),
     Append(Vec<u8>, &'static str),
     Recv(Vec<u8>, &'static str),
     Send(Vec<u8>, &'static str),
     Rng(usize),
 }
 
-impl Hasher for Vec<u8> {
-    fn write(&mut self, buf: &[u8]) {
-        self.extend(buf)
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TraceTranscript<T: Transcript> {
-    ops: Vec<OpType>,
+    ops: Vec<(Option<&'static str>, OpType)>,
     ts: T,
 }
 
@@ -38,11 +56,17 @@ impl<T: Transcript> fmt::Display for TraceTranscript<T> {
 
         let mut ops = self.ops.iter().peekable();
 
-        while let Some(op) = ops.next() {
+        while let Some((label, op)) = ops.next() {
+            if let Some(label) = label {
+                write!(f, "{}: ", label)?;
+            }
             match op {
                 OpType::Challenge(name) => {
                     write!(f, "Challenge({})", name)?;
                 }
+                OpType::Challenges(name, len) => {
+                    write!(f, "Challenges({}; {})", name, len)?;
+                }
                 OpType::Rng(len) => {
                     write!(f, "Rng(Read {} Bytes)", len)?;
                 }
@@ -92,27 +116,35 @@ impl<T: Transcript> TraceTranscript<T> {
 
         // compile the types for each round
         let mut rounds: Vec<(Side, String)> = Vec::new();
-        for op in self.ops.iter() {
+        for (label, op) in self.ops.iter() {
+            let labeled = |name: String| match label {
+                Some(label) => format!("{}: {}", label, name),
+                None => name,
+            };
             match op {
                 OpType::Challenge(name) => {
                     flush(Side::Prover, &mut rounds, &mut prover);
-                    verifier.push(name.to_string())
+                    verifier.push(labeled(name.to_string()))
+                }
+                OpType::Challenges(name, len) => {
+                    flush(Side::Prover, &mut rounds, &mut prover);
+                    verifier.push(labeled(format!("[{}; {}]", name, len)))
                 }
                 OpType::Rng(len) => {
                     flush(Side::Prover, &mut rounds, &mut prover);
-                    verifier.push(format!("[u8;{}]", len))
+                    verifier.push(labeled(format!("[u8;{}]", len)))
                 }
                 OpType::Append(_value, name) => {
                     flush(Side::Verifier, &mut rounds, &mut verifier);
-                    prover.push(name.to_string())
+                    prover.push(labeled(name.to_string()))
                 }
                 OpType::Recv(_value, name) => {
                     flush(Side::Verifier, &mut rounds, &mut verifier);
-                    prover.push(name.to_string())
+                    prover.push(labeled(name.to_string()))
                 }
                 OpType::Send(_value, name) => {
                     flush(Side::Verifier, &mut rounds, &mut verifier);
-                    prover.push(name.to_string())
+                    prover.push(labeled(name.to_string()))
                 }
             }
         }
@@ -160,24 +192,24 @@ impl<T: Transcript> TraceTranscript<T> {
 impl<T: Transcript> RngCore for TraceTranscript<T> {
     fn fill_bytes(&mut self, dest: &mut [u8]) {
         self.ts.fill_bytes(dest);
-        self.ops.push(OpType::Rng(dest.len()));
+        self.ops.push((None, OpType::Rng(dest.len())));
     }
 
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
         let res = self.ts.try_fill_bytes(dest);
-        self.ops.push(OpType::Rng(dest.len()));
+        self.ops.push((None, OpType::Rng(dest.len())));
         res
     }
 
     fn next_u32(&mut self) -> u32 {
         let val = self.ts.next_u32();
-        self.ops.push(OpType::Challenge(type_name::<u32>()));
+        self.ops.push((None, OpType::Challenge(type_name::<u32>())));
         val
     }
 
     fn next_u64(&mut self) -> u64 {
         let val = self.ts.next_u64();
-        self.ops.push(OpType::Challenge(type_name::<u64>()));
+        self.ops.push((None, OpType::Challenge(type_name::<u64>())));
         val
     }
 }
@@ -190,27 +222,156 @@ impl<T: Transcript> Transcript for TraceTranscript<T> {
     fn append<A: Absorb>(&mut self, elem: &A) {
         // add to operations
         {
-            let mut hsh = Vec::new();
-            elem.absorb(&mut hsh);
-            self.ops.push(OpType::Append(hsh, type_name::<A>()));
+            let hsh = absorbed(None, elem);
+            self.ops.push((None, OpType::Append(hsh, type_name::<A>())));
         }
 
         // pass on
         self.ts.append(elem)
     }
 
+    fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+        // add to operations
+        {
+            let hsh = try_absorbed(elem)?;
+            self.ops.push((None, OpType::Append(hsh, type_name::<A>())));
+        }
+
+        // pass on
+        self.ts.try_append(elem)
+    }
+
     fn challenge<C: Challenge>(&mut self) -> C {
         let c = self.ts.challenge();
-        self.ops.push(OpType::Challenge(type_name::<C>()));
+        self.ops.push((None, OpType::Challenge(type_name::<C>())));
+        c
+    }
+
+    fn separate(&mut self, sep: Separator, label: &str) {
+        self.ts.separate(sep, label)
+    }
+
+    fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+        // add to operations
+        {
+            let hsh = absorbed(Some(label), elem);
+            self.ops
+                .push((Some(label), OpType::Append(hsh, type_name::<A>())));
+        }
+
+        // pass on
+        self.ts.append_labeled(label, elem)
+    }
+
+    fn recv_labeled<A: Absorb>(&mut self, label: &'static str, msg: Msg<A>) -> A {
+        // add to operations
+        {
+            let hsh = absorbed(Some(label), &msg.0);
+            self.ops
+                .push((Some(label), OpType::Recv(hsh, type_name::<A>())));
+        }
+
+        // pass on
+        self.ts.recv_labeled(label, msg)
+    }
+
+    fn send_labeled<A: Absorb>(&mut self, label: &'static str, elem: A) -> Msg<A> {
+        // add to operations
+        {
+            let hsh = absorbed(Some(label), &elem);
+            self.ops
+                .push((Some(label), OpType::Send(hsh, type_name::<A>())));
+        }
+
+        // pass on
+        self.ts.send_labeled(label, elem)
+    }
+
+    fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+        let c = self.ts.challenge_labeled(label);
+        self.ops
+            .push((Some(label), OpType::Challenge(type_name::<C>())));
+        c
+    }
+
+    fn challenge_bytes(&mut self, dest: &mut [u8]) {
+        self.ts.challenge_bytes(dest);
+        self.ops
+            .push((None, OpType::Challenges(type_name::<u8>(), dest.len())));
+    }
+
+    fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+        self.ts.challenge_bytes_labeled(label, dest);
+        self.ops.push((
+            Some(label),
+            OpType::Challenges(type_name::<u8>(), dest.len()),
+        ));
+    }
+
+    fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+        // add to operations
+        {
+            let hsh = absorbed(Some(label), &bytes);
+            self.ops
+                .push((Some(label), OpType::Append(hsh, type_name::<&[u8]>())));
+        }
+
+        // pass on
+        self.ts.append_bytes_labeled(label, bytes)
+    }
+
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        // add to operations (using the canonical packing)
+        {
+            let mut hsh = alloc::vec![crate::transcript::FRAME_FIELD];
+            hsh.extend(crate::transcript::field_len(elem).to_le_bytes());
+            elem.absorb_field(&mut crate::Packed(&mut hsh));
+            self.ops.push((None, OpType::Append(hsh, type_name::<A>())));
+        }
+
+        // pass on
+        self.ts.append_field(elem)
+    }
+
+    #[cfg(feature = "ff")]
+    fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+        let c = self.ts.challenge_field();
+        self.ops.push((None, OpType::Challenge(type_name::<F>())));
+        c
+    }
+
+    fn challenge_fill<C: Challenge>(&mut self, dest: &mut [C]) {
+        self.ts.challenge_fill(dest);
+        self.ops
+            .push((None, OpType::Challenges(type_name::<C>(), dest.len())));
+    }
+
+    fn challenge_vec<C: Challenge>(&mut self, n: usize) -> Vec<C> {
+        let c = self.ts.challenge_vec(n);
+        self.ops
+            .push((None, OpType::Challenges(type_name::<C>(), n)));
+        c
+    }
+
+    fn challenge_range(&mut self, n: u64) -> u64 {
+        let c = self.ts.challenge_range(n);
+        self.ops.push((None, OpType::Challenge(type_name::<u64>())));
+        c
+    }
+
+    fn challenge_indices(&mut self, n: u64, k: usize, mode: IndexSampling) -> Vec<u64> {
+        let c = self.ts.challenge_indices(n, k, mode);
+        self.ops
+            .push((None, OpType::Challenge(type_name::<Vec<u64>>())));
         c
     }
 
     fn recv<A: Absorb>(&mut self, msg: Msg<A>) -> A {
         // add to operations
         {
-            let mut hsh = Vec::new();
-            msg.0.absorb(&mut hsh);
-            self.ops.push(OpType::Recv(hsh, type_name::<A>()));
+            let hsh = absorbed(None, &msg.0);
+            self.ops.push((None, OpType::Recv(hsh, type_name::<A>())));
         }
 
         // pass on
@@ -220,12 +381,111 @@ impl<T: Transcript> Transcript for TraceTranscript<T> {
     fn send<A: Absorb>(&mut self, elem: A) -> Msg<A> {
         // add to operations
         {
-            let mut hsh = Vec::new();
-            elem.absorb(&mut hsh);
-            self.ops.push(OpType::Send(hsh, type_name::<A>()));
+            let hsh = absorbed(None, &elem);
+            self.ops.push((None, OpType::Send(hsh, type_name::<A>())));
         }
 
         // pass on
         self.ts.send(elem)
     }
+
+    fn try_recv<A: Absorb>(&mut self, msg: Msg<A>) -> Result<A, AbsorbError> {
+        // add to operations
+        {
+            let hsh = try_absorbed(&msg.0)?;
+            self.ops.push((None, OpType::Recv(hsh, type_name::<A>())));
+        }
+
+        // pass on
+        self.ts.try_recv(msg)
+    }
+}
+
+impl<T: ProofStream> ProofStream for TraceTranscript<T> {
+    fn read<A: Absorb + DeserializeOwned>(&mut self) -> Result<A, DecodeError> {
+        let elem = self.ts.read()?;
+
+        // add to operations
+        let hsh = absorbed(None, &elem);
+        self.ops.push((None, OpType::Recv(hsh, type_name::<A>())));
+        Ok(elem)
+    }
+
+    fn read_labeled<A: Absorb + DeserializeOwned>(
+        &mut self,
+        label: &'static str,
+    ) -> Result<A, DecodeError> {
+        let elem = self.ts.read_labeled(label)?;
+
+        // add to operations
+        let hsh = absorbed(Some(label), &elem);
+        self.ops
+            .push((Some(label), OpType::Recv(hsh, type_name::<A>())));
+        Ok(elem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn labeled_ops() {
+        use super::*;
+        use crate::{KeccakSponge, SpongeTranscript};
+
+        let mut ts = TraceTranscript::new(SpongeTranscript::<KeccakSponge>::new("bevis"));
+        ts.append(&7u8);
+        ts.append_labeled("n", &7u8);
+        let _: u64 = ts.challenge_labeled("c");
+
+        // the recorded bytes include the frames (with the lengths) and the label
+        let plain = alloc::vec![0, 1, 0, 0, 0, 0, 0, 0, 0, 7];
+        let mut labeled = alloc::vec![4, 1, 0, 0, 0, 0, 0, 0, 0, b'n'];
+        labeled.extend_from_slice(&plain);
+
+        assert_eq!(
+            ts.ops,
+            [
+                (None, OpType::Append(plain, type_name::<u8>())),
+                (Some("n"), OpType::Append(labeled, type_name::<u8>())),
+                (Some("c"), OpType::Challenge(type_name::<u64>())),
+            ]
+        );
+        assert_eq!(
+            ts.to_string(),
+            "Transcript(Append(u8), n: Append(u8), c: Challenge(u64))"
+        );
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn grind() {
+        use super::*;
+        use crate::{KeccakSponge, SpongeTranscript};
+
+        let mut direct = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let nonce = direct.grind(8);
+
+        // the trace records the sent nonce (the candidates are tried on the forked child)
+        let mut ts = TraceTranscript::new(SpongeTranscript::<KeccakSponge>::new("bevis"));
+        assert_eq!(ts.grind(8).0, nonce.0);
+        assert_eq!(ts.challenge::<u64>(), direct.challenge::<u64>());
+        assert_eq!(ts.to_string(), "Transcript(Send(u64), Challenge(u64))");
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn challenge_vec() {
+        use super::*;
+        use crate::{KeccakSponge, SpongeTranscript};
+
+        let mut direct = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let expected: Vec<u32> = (0..5).map(|_| direct.challenge()).collect();
+
+        // a single operation for the entire vector
+        let mut ts = TraceTranscript::new(SpongeTranscript::<KeccakSponge>::new("bevis"));
+        assert_eq!(ts.challenge_vec::<u32>(5), expected);
+        assert_eq!(ts.ops, [(None, OpType::Challenges(type_name::<u32>(), 5))]);
+        assert_eq!(ts.to_string(), "Transcript(Challenges(u32; 5))");
+    }
 }
@@ -1,8 +1,125 @@
-use crate::{Absorb, Challenge, Msg, Sampler, Sponge};
+use crate::{Absorb, AbsorbError, Challenge, Hasher, Msg, Sampler, Sponge};
+
+use core::marker::PhantomData;
 
 use rand_core::{CryptoRng, RngCore};
 
-#[derive(Debug)]
+#[cfg(feature = "alloc")]
+use crate::IndexSampling;
+
+// label of the child transcript keying the proof-of-work
+pub(crate) const GRIND: &str = "bevis-grind";
+
+// frame tags of a `SpongeTranscript`: every absorb starts with one byte,
+// distinguishing messages, field elements and the `Separator`s,
+// followed by the length of its content, hence every frame is self-delimiting
+// and no sequence of absorbs produces the frames of another.
+pub(crate) const FRAME_APPEND: u8 = 0;
+#[cfg(feature = "ff")]
+pub(crate) const FRAME_FIELD: u8 = 1;
+
+// proof-of-work function: a copy of the grinding child absorbs the nonce
+// and squeezes a `u64` which must have at least `bits` leading zeros
+pub(crate) fn pow<T: Transcript + Clone>(child: &T, bits: u32, nonce: u64) -> bool {
+    let mut ts = child.clone();
+    ts.append(&nonce);
+    ts.challenge::<u64>().leading_zeros() >= bits
+}
+
+/// Domain separators absorbed by `Transcript::separate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Separator {
+    /// Label of a labelled operation (`append_labeled`, `challenge_labeled`, ...)
+    Label,
+    /// Child branch of a `fork`
+    ForkChild,
+    /// Parent branch of a `fork`
+    ForkParent,
+}
+
+impl Separator {
+    fn frame(self) -> u8 {
+        match self {
+            Separator::ForkChild => 2,
+            Separator::ForkParent => 3,
+            Separator::Label => 4,
+        }
+    }
+}
+
+/// Writes a separator framed as by `SpongeTranscript::separate`:
+/// the frame tag of the separator followed by the (length-prefixed) label.
+pub(crate) fn write_separator<H: Hasher>(h: &mut H, sep: Separator, label: &str) {
+    h.write(&[sep.frame()]);
+    label.absorb(h);
+}
+
+/// Writes a message framed as by `SpongeTranscript::append`:
+/// `FRAME_APPEND` followed by the length of the encoding and the encoding.
+pub(crate) fn write_append<H: Hasher, A: Absorb>(h: &mut H, elem: &A) {
+    let mut len = Len::<H>::new();
+    elem.absorb(&mut len);
+    h.write(&[FRAME_APPEND]);
+    h.write(&len.0.to_le_bytes());
+    elem.absorb(h);
+}
+
+/// `write_append` returning an error (before writing anything)
+/// if the message cannot be absorbed.
+pub(crate) fn try_write_append<H: Hasher, A: Absorb>(
+    h: &mut H,
+    elem: &A,
+) -> Result<(), AbsorbError> {
+    let mut len = Len::<H>::new();
+    elem.try_absorb(&mut len)?;
+    h.write(&[FRAME_APPEND]);
+    h.write(&len.0.to_le_bytes());
+    elem.try_absorb(h)
+}
+
+/// Number of field elements absorbed by `AbsorbField::absorb_field`
+/// (framing the elements as by `SpongeTranscript::append_field`).
+#[cfg(feature = "ff")]
+pub(crate) fn field_len<F: ff::PrimeField, A: crate::AbsorbField<F>>(elem: &A) -> u64 {
+    struct Count(u64);
+
+    impl<F> crate::FieldHasher<F> for Count {
+        fn write_field(&mut self, elems: &[F]) {
+            self.0 += elems.len() as u64;
+        }
+    }
+
+    let mut count = Count(0);
+    elem.absorb_field(&mut count);
+    count.0
+}
+
+// hasher counting the bytes of an encoding in the mode of `H`
+struct Len<H>(u64, PhantomData<H>);
+
+impl<H> Len<H> {
+    fn new() -> Self {
+        Len(0, PhantomData)
+    }
+}
+
+impl<H: Hasher> Hasher for Len<H> {
+    const TAGGED: bool = H::TAGGED;
+
+    fn write(&mut self, buf: &[u8]) {
+        self.0 += buf.len() as u64;
+    }
+}
+
+// hasher discarding its input
+struct Discard;
+
+impl Hasher for Discard {
+    fn write(&mut self, _buf: &[u8]) {}
+}
+
+#[derive(Debug, Clone)]
 #[repr(transparent)]
 pub struct SpongeTranscript<S: Sponge>(S);
 
@@ -10,9 +127,159 @@ pub trait Transcript: Sampler + Sized {
     /// Append message to the trancript
     fn append<A: Absorb>(&mut self, elem: &A);
 
+    /// Append message to the transcript,
+    /// returns an error if the message cannot be absorbed.
+    ///
+    /// After an error the transcript must be discarded.
+    ///
+    /// By default the message is encoded once without absorbing it
+    /// (to detect errors) before being appended using `append`,
+    /// transcripts should override this to absorb the message only once.
+    fn try_append<A: Absorb>(&mut self, elem: &A) -> Result<(), AbsorbError> {
+        elem.try_absorb(&mut Discard)?;
+        self.append(elem);
+        Ok(())
+    }
+
     /// Generate a challenge
     fn challenge<C: Challenge>(&mut self) -> C;
 
+    /// Absorb a domain separator and a label.
+    ///
+    /// By default the separator is appended as a message,
+    /// hence it is only as distinct from appended messages as the encoding allows:
+    /// transcripts should override this to frame separators apart from messages
+    /// (as `SpongeTranscript` does).
+    fn separate(&mut self, sep: Separator, label: &str) {
+        self.append(&(sep.frame(), label))
+    }
+
+    /// Fork the transcript: returns an independent child transcript
+    /// bound to the current state and `label`.
+    ///
+    /// The child is a copy of the transcript,
+    /// then both branches absorb a (distinct) `Separator` and the label,
+    /// hence the parent remains usable and diverges from the child,
+    /// and children with different labels diverge from each other.
+    fn fork(&mut self, label: &str) -> Self
+    where
+        Self: Clone,
+    {
+        let mut child = self.clone();
+        child.separate(Separator::ForkChild, label);
+        self.separate(Separator::ForkParent, label);
+        child
+    }
+
+    /// Append a labelled message:
+    /// the label is absorbed as a `Separator::Label` before the message,
+    /// hence it cannot be confused with an appended message
+    /// if the transcript frames separators apart from messages (see `separate`).
+    fn append_labeled<A: Absorb>(&mut self, label: &'static str, elem: &A) {
+        self.separate(Separator::Label, label);
+        self.append(elem);
+    }
+
+    /// Receive a labelled message (see `append_labeled`)
+    fn recv_labeled<A: Absorb>(&mut self, label: &'static str, msg: Msg<A>) -> A {
+        self.append_labeled(label, &msg.0);
+        msg.0
+    }
+
+    /// Send a labelled message (see `append_labeled`)
+    fn send_labeled<A: Absorb>(&mut self, label: &'static str, elem: A) -> Msg<A> {
+        self.append_labeled(label, &elem);
+        Msg(elem)
+    }
+
+    /// Generate a labelled challenge:
+    /// the label is absorbed as a `Separator::Label` before sampling.
+    fn challenge_labeled<C: Challenge>(&mut self, label: &'static str) -> C {
+        self.separate(Separator::Label, label);
+        self.challenge()
+    }
+
+    /// Fill `dest` with challenge bytes, squeezed in a single operation.
+    ///
+    /// Prefer this over `challenge::<[u8; N]>()`, which samples every byte separately.
+    fn challenge_bytes(&mut self, dest: &mut [u8]) {
+        self.fill_bytes(dest)
+    }
+
+    /// Fill `dest` with labelled challenge bytes (see `challenge_labeled`)
+    fn challenge_bytes_labeled(&mut self, label: &'static str, dest: &mut [u8]) {
+        self.separate(Separator::Label, label);
+        self.challenge_bytes(dest)
+    }
+
+    /// Append a labelled byte string.
+    ///
+    /// Equivalent to `append_labeled(label, &bytes)`, except for transcripts with native
+    /// byte-string messages (e.g. `MerlinTranscript`) which absorb the bytes as-is.
+    fn append_bytes_labeled(&mut self, label: &'static str, bytes: &[u8]) {
+        self.append_labeled(label, &bytes)
+    }
+
+    /// Append field elements (see `AbsorbField`).
+    ///
+    /// Sponge transcripts absorb them natively if the sponge is algebraic over `F`
+    /// and as their canonical byte packing otherwise (see `Sponge::write_elems`),
+    /// by default every element is appended as `Ff(elem)`.
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        // appends every element individually
+        struct Elems<'a, T>(&'a mut T);
+
+        impl<F: ff::PrimeField, T: Transcript> crate::FieldHasher<F> for Elems<'_, T> {
+            fn write_field(&mut self, elems: &[F]) {
+                for elem in elems {
+                    self.0.append(&crate::Ff(*elem))
+                }
+            }
+        }
+
+        elem.absorb_field(&mut Elems(self))
+    }
+
+    /// Generate a field element challenge
+    ///
+    /// Sponge transcripts squeeze it natively if the sponge is algebraic over `F`
+    /// (see `Sponge::squeeze_elem`), by default it is sampled as `Ff<F>`.
+    #[cfg(feature = "ff")]
+    fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+        self.challenge::<crate::Ff<F>>().into_inner()
+    }
+
+    /// Fill a slice with challenges (runtime length)
+    fn challenge_fill<C: Challenge>(&mut self, dest: &mut [C]) {
+        for c in dest.iter_mut() {
+            *c = C::sample(self);
+        }
+    }
+
+    /// Generate a vector of `n` challenges (runtime length)
+    #[cfg(feature = "alloc")]
+    fn challenge_vec<C: Challenge>(&mut self, n: usize) -> alloc::vec::Vec<C> {
+        (0..n).map(|_| C::sample(self)).collect()
+    }
+
+    /// Generate a challenge uniform in `[0, n)` (without modulo bias)
+    ///
+    /// Panics if `n` is zero.
+    fn challenge_range(&mut self, n: u64) -> u64 {
+        crate::challenge::sample_below(self, n)
+    }
+
+    /// Generate `k` query indices from `[0, n)`, sorted and deduplicated
+    ///
+    /// With `IndexSampling::Distinct` exactly `k` indices are returned (panics if `k > n`),
+    /// with `IndexSampling::WithReplacement` duplicates are removed, hence at most `k`
+    /// (panics if `n` is zero and `k` is not).
+    #[cfg(feature = "alloc")]
+    fn challenge_indices(&mut self, n: u64, k: usize, mode: IndexSampling) -> alloc::vec::Vec<u64> {
+        crate::challenge::sample_indices(self, n, k, mode)
+    }
+
     fn recv<A: Absorb>(&mut self, msg: Msg<A>) -> A {
         self.append(&msg.0);
         msg.0
@@ -22,12 +289,63 @@ pub trait Transcript: Sampler + Sized {
         self.append(&elem);
         Msg(elem)
     }
+
+    /// Proof-of-work (prover): fork a child transcript (labelled `"bevis-grind"`)
+    /// and find a nonce such that a copy of the child absorbing the nonce
+    /// squeezes a `u64` with at least `bits` leading zero bits, then send the nonce.
+    ///
+    /// The transcript is forked once, candidates are only tried on copies of the child,
+    /// expected work is `2^bits` trials (matching `verify_grind`).
+    fn grind(&mut self, bits: u32) -> Msg<u64>
+    where
+        Self: Clone,
+    {
+        assert!(bits <= 64, "at most 64 bits of grinding");
+        let child = self.fork(GRIND);
+        let nonce = (0u64..).find(|nonce| pow(&child, bits, *nonce)).unwrap();
+        self.send(nonce)
+    }
+
+    /// Proof-of-work (verifier): fork the child, receive the nonce and check it
+    /// (see `grind`).
+    ///
+    /// Forks and absorbs the nonce even if the check fails.
+    fn verify_grind(&mut self, bits: u32, nonce: Msg<u64>) -> bool
+    where
+        Self: Clone,
+    {
+        assert!(bits <= 64, "at most 64 bits of grinding");
+        let child = self.fork(GRIND);
+        let nonce = self.recv(nonce);
+        pow(&child, bits, nonce)
+    }
+
+    /// Receive a message,
+    /// returns an error if the message cannot be absorbed.
+    fn try_recv<A: Absorb>(&mut self, msg: Msg<A>) -> Result<A, AbsorbError> {
+        self.try_append(&msg.0)?;
+        Ok(msg.0)
+    }
 }
 
 impl<S: Sponge> SpongeTranscript<S> {
     pub fn new(sep: &str) -> Self {
         Self(S::new(sep))
     }
+
+    /// Create a transcript which absorbs and enforces the given I/O pattern
+    #[cfg(feature = "alloc")]
+    pub fn with_pattern(sep: &str, pattern: crate::IoPattern) -> crate::PatternTranscript<Self> {
+        crate::PatternTranscript::new(Self::new(sep), pattern)
+    }
+}
+
+impl<S: Sponge> From<S> for SpongeTranscript<S> {
+    /// Transcript over an already initialized sponge
+    /// (e.g. `PoseidonSponge::with_params`)
+    fn from(sponge: S) -> Self {
+        Self(sponge)
+    }
 }
 
 impl<S: Sponge> RngCore for SpongeTranscript<S> {
@@ -54,11 +372,364 @@ impl<S: Sponge> Sampler for SpongeTranscript<S> {}
 
 impl<S: Sponge> Transcript for SpongeTranscript<S> {
     fn append<T: Absorb>(&mut self, elem: &T) {
-        elem.absorb(&mut self.0);
+        write_append(&mut self.0, elem)
+    }
+
+    fn try_append<T: Absorb>(&mut self, elem: &T) -> Result<(), AbsorbError> {
+        try_write_append(&mut self.0, elem)
     }
 
     /// Sends a challenge to the prover
     fn challenge<T: Challenge>(&mut self) -> T {
         T::sample(&mut self.0)
     }
+
+    fn separate(&mut self, sep: Separator, label: &str) {
+        write_separator(&mut self.0, sep, label)
+    }
+
+    #[cfg(feature = "ff")]
+    fn append_field<F: ff::PrimeField, A: crate::AbsorbField<F>>(&mut self, elem: &A) {
+        self.0.write(&[FRAME_FIELD]);
+        self.0.write(&field_len(elem).to_le_bytes());
+        elem.absorb_field(&mut Elems(&mut self.0))
+    }
+
+    #[cfg(feature = "ff")]
+    fn challenge_field<F: ff::PrimeField>(&mut self) -> F {
+        self.0.squeeze_elem()
+    }
+}
+
+// absorbs field elements into a sponge using `Sponge::write_elems`
+#[cfg(feature = "ff")]
+struct Elems<'a, S>(&'a mut S);
+
+#[cfg(feature = "ff")]
+impl<F: ff::PrimeField, S: Sponge> crate::FieldHasher<F> for Elems<'_, S> {
+    fn write_field(&mut self, elems: &[F]) {
+        self.0.write_elems(elems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn grind() {
+        use super::*;
+        use crate::KeccakSponge;
+
+        let mut prover = SpongeTranscript::<KeccakSponge>::new("bevis");
+        prover.append(&1u64);
+        let nonce = prover.grind(8);
+
+        // the prover returns the first valid nonce
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        ts.append(&1u64);
+        let child = ts.fork(GRIND);
+        assert!(pow(&child, 8, nonce.0));
+        assert!((0..nonce.0).all(|n| !pow(&child, 8, n)));
+
+        let mut verifier = SpongeTranscript::<KeccakSponge>::new("bevis");
+        verifier.append(&1u64);
+        assert!(verifier.verify_grind(8, Msg(nonce.0)));
+        assert_eq!(prover.challenge::<u64>(), verifier.challenge::<u64>());
+
+        // a smaller nonce fails
+        if nonce.0 > 0 {
+            let mut verifier = SpongeTranscript::<KeccakSponge>::new("bevis");
+            verifier.append(&1u64);
+            assert!(!verifier.verify_grind(8, Msg(nonce.0 - 1)));
+        }
+
+        // zero bits accepts any nonce
+        let mut verifier = SpongeTranscript::<KeccakSponge>::new("bevis");
+        assert!(verifier.verify_grind(0, Msg(12345)));
+    }
+
+    #[cfg(all(feature = "keccak", feature = "alloc"))]
+    #[test]
+    fn challenge_vec() {
+        use super::*;
+        use crate::KeccakSponge;
+
+        // equal to sampling every challenge individually
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let c: alloc::vec::Vec<u64> = ts.challenge_vec(4);
+        let mut expected = SpongeTranscript::<KeccakSponge>::new("bevis");
+        assert_eq!(
+            c,
+            (0..4)
+                .map(|_| expected.challenge())
+                .collect::<alloc::vec::Vec<u64>>()
+        );
+        assert_eq!(ts.challenge::<u64>(), expected.challenge::<u64>());
+
+        assert!(ts.challenge_vec::<u64>(0).is_empty());
+        assert_eq!(ts.challenge::<u64>(), expected.challenge::<u64>());
+    }
+
+    #[cfg(feature = "poseidon")]
+    #[test]
+    fn grind_poseidon() {
+        use super::*;
+        use crate::PoseidonSponge;
+        use pasta_curves::Fp;
+
+        // grinding is generic over the sponge of the transcript
+        let mut prover = SpongeTranscript::<PoseidonSponge<Fp>>::new("bevis");
+        let nonce = prover.grind(4);
+
+        let mut verifier = SpongeTranscript::<PoseidonSponge<Fp>>::new("bevis");
+        assert!(verifier.verify_grind(4, Msg(nonce.0)));
+        assert_eq!(prover.challenge::<u64>(), verifier.challenge::<u64>());
+    }
+
+    #[cfg(all(feature = "keccak", feature = "safe"))]
+    #[test]
+    fn grind_through_arthur() {
+        use super::*;
+        use crate::{Arthur, KeccakSponge, Merlin};
+
+        let mut direct = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let nonce = direct.grind(8);
+        let c: u64 = direct.challenge();
+
+        // the wrappers grind on (copies of) the wrapped transcript
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let mut merlin = Merlin::new(&mut ts);
+        assert_eq!(merlin.grind(8).0, nonce.0);
+        assert_eq!(merlin.challenge::<u64>(), c);
+
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let mut arthur = Arthur::new(&mut ts);
+        assert!(arthur.verify_grind(8, Msg(nonce.0)));
+        assert_eq!(arthur.challenge::<u64>(), c);
+    }
+
+    #[cfg(feature = "poseidon")]
+    #[test]
+    fn field_native() {
+        use super::*;
+        use crate::{FieldHasher, FieldSampler, PoseidonSponge};
+        use pasta_curves::Fp;
+
+        type Pasta = PoseidonSponge<Fp>;
+
+        let mut sponge = Pasta::new("bevis");
+        sponge.write(&[FRAME_FIELD]);
+        sponge.write(&2u64.to_le_bytes());
+        sponge.write_field(&[Fp::from(1), Fp::from(2)]);
+        let expected: Fp = sponge.squeeze_field();
+
+        let mut ts = SpongeTranscript::<Pasta>::new("bevis");
+        ts.append_field(&[Fp::from(1), Fp::from(2)]);
+        assert_eq!(ts.challenge_field::<Fp>(), expected);
+    }
+
+    #[cfg(all(feature = "keccak", feature = "ff"))]
+    #[test]
+    fn field_packed() {
+        use super::*;
+        use crate::{FieldHasher, KeccakSponge, Packed};
+        use pasta_curves::Fp;
+
+        let mut sponge = KeccakSponge::<136>::new("bevis");
+        sponge.write(&[FRAME_FIELD]);
+        sponge.write(&1u64.to_le_bytes());
+        Packed(&mut sponge).write_field(&[Fp::from(7)]);
+        let expected = crate::Ff::<Fp>::sample(&mut sponge).into_inner();
+
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        ts.append_field(&[Fp::from(7)]);
+        assert_eq!(ts.challenge_field::<Fp>(), expected);
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn fork() {
+        use super::*;
+        use crate::KeccakSponge;
+
+        let mut parent = SpongeTranscript::<KeccakSponge>::new("bevis");
+        parent.append(&1u64);
+        let mut base = parent.clone();
+
+        let mut child = parent.fork("a");
+        let mut other = base.clone().fork("b");
+        let mut again = base.clone().fork("a");
+
+        let c: u64 = child.challenge();
+        assert_eq!(again.challenge::<u64>(), c);
+        assert_ne!(other.challenge::<u64>(), c);
+        assert_ne!(parent.challenge::<u64>(), c);
+
+        // the separators cannot be produced by appending their encoding
+        let mut forged = base.clone();
+        forged.append(&(2u8, "a"));
+        assert_ne!(forged.challenge::<u64>(), c);
+
+        // nor by appending the label
+        base.append(&"a");
+        assert_ne!(base.challenge::<u64>(), c);
+
+        // nor by a message continuing the encoding of a preceding message
+        let mut prefixed = SpongeTranscript::<KeccakSponge>::new("bevis");
+        prefixed.append(&0u8);
+        let mut child = prefixed.fork("a");
+        let mut forged = SpongeTranscript::<KeccakSponge>::new("bevis");
+        forged.append(&(0u8, 2u8, "a"));
+        assert_ne!(forged.challenge::<u64>(), child.challenge::<u64>());
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn labels() {
+        use super::*;
+        use crate::KeccakSponge;
+
+        let base = SpongeTranscript::<KeccakSponge>::new("bevis");
+
+        let mut labeled = base.clone();
+        labeled.append_labeled("x", &1u64);
+        let c: u64 = labeled.challenge();
+
+        // the label is not an appended message
+        let mut plain = base.clone();
+        plain.append(&"x");
+        plain.append(&1u64);
+        assert_ne!(plain.challenge::<u64>(), c);
+
+        let mut other = base.clone();
+        other.append_labeled("y", &1u64);
+        assert_ne!(other.challenge::<u64>(), c);
+
+        let mut labeled = base.clone();
+        let c: u64 = labeled.challenge_labeled("x");
+        let mut plain = base.clone();
+        plain.append(&"x");
+        assert_ne!(plain.challenge::<u64>(), c);
+
+        // nor a message continuing the encoding of a preceding message
+        let mut labeled = base.clone();
+        labeled.append(&0u8);
+        labeled.append_labeled("x", &1u64);
+        let mut forged = base.clone();
+        forged.append(&(0u8, 4u8, "x", 0u8, 1u64));
+        assert_ne!(forged.challenge::<u64>(), labeled.challenge::<u64>());
+
+        // swapping labelled messages changes the transcript
+        let mut ab = base.clone();
+        ab.append_labeled("a", &1u64);
+        ab.append_labeled("b", &2u64);
+        let mut ba = base.clone();
+        ba.append_labeled("b", &1u64);
+        ba.append_labeled("a", &2u64);
+        assert_ne!(ab.challenge::<u64>(), ba.challenge::<u64>());
+    }
+
+    #[cfg(all(feature = "keccak", feature = "safe"))]
+    #[test]
+    fn fork_through_arthur() {
+        use super::*;
+        use crate::{Arthur, KeccakSponge};
+
+        let mut direct = SpongeTranscript::<KeccakSponge>::new("bevis");
+        let mut child = direct.fork("sub");
+        let expected: (u64, u64) = (child.challenge(), direct.challenge());
+
+        // forking requires no `Clone` bound on the (generic) safe transcript
+        fn forked<T: Transcript + crate::Safe>(ts: &mut T) -> (u64, u64) {
+            let mut child = ts.fork("sub");
+            (child.challenge(), ts.challenge())
+        }
+
+        let mut ts = SpongeTranscript::<KeccakSponge>::new("bevis");
+        assert_eq!(forked(&mut Arthur::new(&mut ts)), expected);
+    }
+
+    #[cfg(all(feature = "poseidon", feature = "safe"))]
+    #[test]
+    fn field_through_arthur() {
+        use super::*;
+        use crate::{Arthur, PoseidonSponge};
+        use pasta_curves::Fp;
+
+        type Pasta = PoseidonSponge<Fp>;
+
+        let mut direct = SpongeTranscript::<Pasta>::new("bevis");
+        direct.append_field(&[Fp::from(5)]);
+        let expected: Fp = direct.challenge_field();
+
+        let mut ts = SpongeTranscript::<Pasta>::new("bevis");
+        let mut arthur = Arthur::new(&mut ts);
+        arthur.append_field(&[Fp::from(5)]);
+        assert_eq!(arthur.challenge_field::<Fp>(), expected);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn default_try_append() {
+        use super::*;
+        use crate::{Reason, Transcript};
+
+        // transcript implementing only the required methods
+        struct Bytes(alloc::vec::Vec<u8>);
+
+        impl RngCore for Bytes {
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                dest.fill(0)
+            }
+
+            fn next_u32(&mut self) -> u32 {
+                0
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                0
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        impl CryptoRng for Bytes {}
+
+        impl Sampler for Bytes {}
+
+        impl Transcript for Bytes {
+            fn append<A: Absorb>(&mut self, elem: &A) {
+                elem.absorb(&mut self.0)
+            }
+
+            fn challenge<C: Challenge>(&mut self) -> C {
+                C::sample(self)
+            }
+        }
+
+        // value which fails to serialize
+        struct Failing;
+
+        impl serde::Serialize for Failing {
+            fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("failing"))
+            }
+        }
+
+        let mut ts = Bytes(alloc::vec::Vec::new());
+        ts.try_append(&1u8).unwrap();
+        assert_eq!(ts.0, [1]);
+
+        // nothing is appended when the message cannot be absorbed
+        let err = ts.try_append(&(2u8, Failing)).unwrap_err();
+        assert_eq!(err.reason(), Reason::Custom);
+        assert_eq!(ts.0, [1]);
+
+        // by default the separator and the label are appended as a message
+        ts.append_labeled("x", &3u8);
+        assert_eq!(ts.0, [1, 4, 1, 0, 0, 0, 0, 0, 0, 0, b'x', 3]);
+    }
 }
@@ -0,0 +1,132 @@
+use crate::Challenge;
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use ff::PrimeField;
+
+use rand_core::{CryptoRng, RngCore};
+
+use serde::{de, Deserialize, Serialize};
+
+// additional bytes sampled for challenges, such that the bias after reduction is 2^-128
+const CHALLENGE_SLACK: usize = 16;
+
+// maximum number of bytes sampled for a challenge
+const MAX_CHALLENGE: usize = 96;
+
+/// Bridge for zkcrypto (`ff`) prime fields.
+///
+/// `ts.challenge::<Ff<Scalar>>()` samples a field element with negligible bias:
+/// `ceil(NUM_BITS / 8) + 16` uniform bytes are reduced modulo p (bias 2^-128).
+/// This also covers the scalars of `group::Group` (`Ff<G::Scalar>`).
+///
+/// Serializes (and hence absorbs) the wrapped element as the bytes of its canonical representation,
+/// deserialization rejects non-canonical representations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct Ff<F>(pub F);
+
+impl<F> Ff<F> {
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+}
+
+impl<F> From<F> for Ff<F> {
+    fn from(value: F) -> Self {
+        Ff(value)
+    }
+}
+
+impl<F> Deref for Ff<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F> DerefMut for Ff<F> {
+    fn deref_mut(&mut self) -> &mut F {
+        &mut self.0
+    }
+}
+
+impl<F: PrimeField> Challenge for Ff<F> {
+    fn sample<S: CryptoRng + RngCore>(ts: &mut S) -> Self {
+        let n = (F::NUM_BITS as usize).div_ceil(8) + CHALLENGE_SLACK;
+        assert!(n <= MAX_CHALLENGE, "field too large");
+
+        let mut bytes = [0u8; MAX_CHALLENGE];
+        ts.fill_bytes(&mut bytes[..n]);
+
+        // reduce the little-endian integer modulo p
+        let base = F::from(256);
+        Ff(bytes[..n]
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, v| acc * base + F::from(*v as u64)))
+    }
+}
+
+impl<F: PrimeField> Serialize for Ff<F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.0.to_repr().as_ref())
+    }
+}
+
+struct ReprVisitor<F>(PhantomData<F>);
+
+impl<F: PrimeField> ReprVisitor<F> {
+    fn from_bytes<E: de::Error>(v: &[u8]) -> Result<F, E> {
+        let mut repr = F::Repr::default();
+        if repr.as_ref().len() != v.len() {
+            return Err(E::invalid_length(v.len(), &"canonical representation"));
+        }
+        repr.as_mut().copy_from_slice(v);
+        Option::from(F::from_repr(repr)).ok_or_else(|| E::custom("non-canonical field element"))
+    }
+}
+
+impl<'de, F: PrimeField> de::Visitor<'de> for ReprVisitor<F> {
+    type Value = F;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "canonical representation of a field element")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Self::from_bytes(v)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut repr = F::Repr::default();
+        let len = repr.as_ref().len();
+        for (i, v) in repr.as_mut().iter_mut().enumerate() {
+            *v = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(len + 1, &self));
+        }
+        Option::from(F::from_repr(repr))
+            .ok_or_else(|| de::Error::custom("non-canonical field element"))
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for Ff<F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_bytes(ReprVisitor(PhantomData))
+            .map(Ff)
+    }
+}